@@ -0,0 +1,248 @@
+use crate::weights::ParamWeights;
+use crate::{ChooserError, FormParam, GetRandom, RandomForm, RandomFormOptions};
+use hoplite_verbs_rs::*;
+use rand::Rng;
+use std::collections::{HashSet, VecDeque};
+
+/// One graded answer: the cell that was asked and the params changed to get there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnswerRecord {
+    pub param_hash: u32,
+    pub changed: Vec<FormParam>,
+    pub correct: bool,
+}
+
+/// Raises or lowers the number of changes from recent answers, and changes the params
+/// a student keeps missing more often until they improve.
+#[derive(Debug, Clone)]
+pub struct AdaptiveChooser {
+    pub min_changes: u8,
+    pub max_changes: u8,
+    /// How many recent answers are kept, and how many it takes to move up or down.
+    pub window: usize,
+    /// Move up a change at or above this share of right answers in the window...
+    pub raise_at: f64,
+    /// ...and down one below this share.
+    pub lower_below: f64,
+    /// How much more often a param that is always missed is changed than one never missed.
+    pub max_boost: f64,
+    num_changes: u8,
+    since_adjust: usize,
+    history: VecDeque<AnswerRecord>,
+}
+
+impl AdaptiveChooser {
+    pub fn new(min_changes: u8, max_changes: u8) -> AdaptiveChooser {
+        AdaptiveChooser {
+            min_changes,
+            max_changes,
+            window: 10,
+            raise_at: 0.8,
+            lower_below: 0.5,
+            max_boost: 3.0,
+            num_changes: min_changes,
+            since_adjust: 0,
+            history: VecDeque::new(),
+        }
+    }
+
+    pub fn num_changes(&self) -> u8 {
+        self.num_changes
+    }
+
+    pub fn history(&self) -> &VecDeque<AnswerRecord> {
+        &self.history
+    }
+
+    pub fn record(&mut self, vf: &HcGreekVerbForm, changed: &[FormParam], correct: bool) {
+        self.history.push_back(AnswerRecord {
+            param_hash: vf.param_hash(),
+            changed: changed.to_vec(),
+            correct,
+        });
+        while self.history.len() > self.window {
+            self.history.pop_front();
+        }
+
+        // only move again once a full window has been answered at the new level
+        self.since_adjust += 1;
+        if self.since_adjust < self.window {
+            return;
+        }
+        let accuracy = self.accuracy();
+        if accuracy >= self.raise_at && self.num_changes < self.max_changes {
+            self.num_changes += 1;
+            self.since_adjust = 0;
+        } else if accuracy < self.lower_below && self.num_changes > self.min_changes {
+            self.num_changes -= 1;
+            self.since_adjust = 0;
+        }
+    }
+
+    // share of right answers in the window, 1.0 if nothing has been answered yet
+    pub fn accuracy(&self) -> f64 {
+        if self.history.is_empty() {
+            return 1.0;
+        }
+        self.history.iter().filter(|r| r.correct).count() as f64 / self.history.len() as f64
+    }
+
+    // share of wrong answers when param was one of the changes
+    pub fn error_rate(&self, param: &FormParam) -> Option<f64> {
+        let with_param: Vec<&AnswerRecord> = self
+            .history
+            .iter()
+            .filter(|r| r.changed.contains(param))
+            .collect();
+        if with_param.is_empty() {
+            return None;
+        }
+        Some(with_param.iter().filter(|r| !r.correct).count() as f64 / with_param.len() as f64)
+    }
+
+    // cells answered wrongly in the window
+    pub fn missed_cells(&self) -> HashSet<u32> {
+        self.history
+            .iter()
+            .filter(|r| !r.correct)
+            .map(|r| r.param_hash)
+            .collect()
+    }
+
+    pub fn weights(&self) -> ParamWeights {
+        let mut weights = ParamWeights::default();
+        for p in FormParam::ALL {
+            if let Some(rate) = self.error_rate(&p) {
+                weights.set_weight(&p, 1.0 + (self.max_boost - 1.0) * rate);
+            }
+        }
+        weights
+    }
+
+    // options with this chooser's weights added
+    pub fn options(&self, options: &RandomFormOptions) -> RandomFormOptions {
+        RandomFormOptions {
+            weights: self.weights(),
+            ..options.clone()
+        }
+    }
+
+    pub fn random_form_with_options<R: Rng + ?Sized>(
+        &self,
+        vf: &HcGreekVerbForm,
+        highest_unit: Option<i16>,
+        parameters: &VerbParameters,
+        filter_forms: Option<&HashSet<u32>>,
+        options: &RandomFormOptions,
+        rng: &mut R,
+    ) -> Result<RandomForm, ChooserError> {
+        vf.random_form_with_options(
+            self.num_changes,
+            highest_unit,
+            parameters,
+            filter_forms,
+            &self.options(options),
+            rng,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_adaptive_num_changes() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let vf = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+
+        let mut chooser = AdaptiveChooser::new(1, 3);
+        assert_eq!(chooser.num_changes(), 1);
+        for _i in 0..10 {
+            chooser.record(&vf, &[HcParameters::Person.into()], true);
+        }
+        assert_eq!(chooser.num_changes(), 2);
+        for _i in 0..10 {
+            chooser.record(&vf, &[HcParameters::Person.into()], true);
+        }
+        assert_eq!(chooser.num_changes(), 3);
+        for _i in 0..10 {
+            chooser.record(&vf, &[HcParameters::Person.into()], true);
+        }
+        assert_eq!(chooser.num_changes(), 3);
+
+        for _i in 0..10 {
+            chooser.record(&vf, &[HcParameters::Person.into()], false);
+        }
+        assert_eq!(chooser.num_changes(), 2);
+        assert_eq!(chooser.missed_cells().len(), 1);
+    }
+
+    #[test]
+    fn test_adaptive_weights() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let mut vf = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+
+        // voice changes are always missed, person changes never
+        let mut chooser = AdaptiveChooser::new(1, 1);
+        for _i in 0..5 {
+            chooser.record(&vf, &[HcParameters::Voice.into()], false);
+            chooser.record(&vf, &[HcParameters::Person.into()], true);
+        }
+        let weights = chooser.weights();
+        assert_eq!(weights.voice, 3.0);
+        assert_eq!(weights.person, 1.0);
+        assert_eq!(weights.tense, 1.0);
+
+        let parameters = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present, HcTense::Imperfect, HcTense::Future],
+            voices: vec![HcVoice::Active, HcVoice::Middle, HcVoice::Passive],
+            moods: vec![HcMood::Indicative, HcMood::Subjunctive],
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut voice_changes = 0;
+        let count = 10_000;
+        for _i in 0..count {
+            let changed = vf.change_params_with_options(
+                1,
+                &parameters,
+                &RandomFormOptions {
+                    weights: weights.clone(),
+                    ..Default::default()
+                },
+                &mut rng,
+            );
+            if changed == vec![HcParameters::Voice.into()] {
+                voice_changes += 1;
+            }
+        }
+        // 3 / (1 + 1 + 1 + 1 + 3)
+        let share = voice_changes as f64 / count as f64;
+        assert!(share > 0.40 && share < 0.46, "{}", share);
+    }
+}
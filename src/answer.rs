@@ -0,0 +1,241 @@
+use std::fmt;
+use unicode_normalization::UnicodeNormalization;
+
+const GRAVE: char = '\u{0300}';
+const ACUTE: char = '\u{0301}';
+const MACRON: char = '\u{0304}';
+const BREVE: char = '\u{0306}';
+const SMOOTH: char = '\u{0313}';
+const ROUGH: char = '\u{0314}';
+const CIRCUMFLEX: char = '\u{0342}';
+
+// a difference starting in the last ENDING_LEN letters, after more than half the form
+// is right, counts as a wrong ending
+const ENDING_LEN: usize = 5;
+
+/// What is wrong with an answer, from least to most serious.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mistake {
+    /// Right letters and breathings, wrong accent.
+    AccentOnly,
+    /// Right letters and accents, wrong breathing.
+    BreathingOnly,
+    /// Right letters, but more than one kind of mark is wrong (accent, breathing, iota subscript).
+    Diacritics,
+    /// Right but for a missing syllabic or temporal augment.
+    MissingAugment,
+    /// The stem is right but the ending is not.
+    WrongEnding,
+    WrongStem,
+}
+
+impl fmt::Display for Mistake {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mistake::AccentOnly => write!(f, "only the accent is wrong"),
+            Mistake::BreathingOnly => write!(f, "only the breathing is wrong"),
+            Mistake::Diacritics => write!(f, "the accent or breathing marks are wrong"),
+            Mistake::MissingAugment => write!(f, "the augment is missing"),
+            Mistake::WrongEnding => write!(f, "the ending is wrong"),
+            Mistake::WrongStem => write!(f, "the stem is wrong"),
+        }
+    }
+}
+
+// decomposed, lower case, final sigma as sigma, without vowel length marks or extra spaces
+// oxia and tonos decompose to the same acute
+pub fn normalize(s: &str) -> String {
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .nfd()
+        .filter(|c| *c != MACRON && *c != BREVE)
+        .map(|c| if c == 'ς' { 'σ' } else { c })
+        .collect()
+}
+
+/// Every accepted spelling of a form from `get_form`, normalized: each comma-separated
+/// form, and with and without a movable ν, whether written ν or (ν).
+pub fn alternatives(form: &str) -> Vec<String> {
+    let mut alts = vec![];
+    for f in form.split(',').map(normalize).filter(|f| !f.is_empty()) {
+        let stem = f.strip_suffix("(ν)").or_else(|| f.strip_suffix('ν'));
+        match stem {
+            Some(stem) if f.ends_with("(ν)") || takes_movable_nu(stem) => {
+                alts.push(stem.to_string());
+                alts.push(format!("{}ν", stem));
+            }
+            _ if takes_movable_nu(&f) => {
+                alts.push(format!("{}ν", f));
+                alts.push(f);
+            }
+            _ => alts.push(f),
+        }
+    }
+    alts
+}
+
+/// Whether two forms from `get_form` share an accepted spelling, e.g. "λύσει, λύσῃ" and "λύσῃ".
+pub fn same_form(a: &str, b: &str) -> bool {
+    let b = alternatives(b);
+    alternatives(a).iter().any(|alt| b.contains(alt))
+}
+
+// a normalized form without its ν that can take one: a dative plural or third person plural
+// in -σι (-ξι, -ψι), or a third person singular in an unaccented -σε or -κε (-ξε, -ψε)
+// other forms in -ε, like the imperative λῦε, and those in -μεν, like λύομεν, don't
+fn takes_movable_nu(stem: &str) -> bool {
+    let last: Vec<char> = stem.chars().rev().take(2).collect();
+    matches!(
+        last[..],
+        ['ι', 'σ' | 'ξ' | 'ψ'] | ['ε', 'σ' | 'κ' | 'ξ' | 'ψ']
+    )
+}
+
+/// Checks a typed answer against a form from `get_form`.
+/// If none of the alternatives match, the mistake is the least serious one against any of them.
+pub fn check_answer(answer: &str, form: &str) -> Result<(), Mistake> {
+    let answer = normalize(answer);
+    let alts = alternatives(form);
+    if alts.contains(&answer) {
+        return Ok(());
+    }
+    Err(alts
+        .iter()
+        .map(|alt| classify(&answer, alt))
+        .min()
+        .unwrap_or(Mistake::WrongStem))
+}
+
+fn without(s: &str, marks: &[char]) -> String {
+    s.chars().filter(|c| !marks.contains(c)).collect()
+}
+
+// letters only, without any combining marks
+fn bare(s: &str) -> Vec<char> {
+    s.chars()
+        .filter(|c| !('\u{0300}'..='\u{036f}').contains(c))
+        .collect()
+}
+
+// answer and expected are normalized and differ
+fn classify(answer: &str, expected: &str) -> Mistake {
+    let accents = [GRAVE, ACUTE, CIRCUMFLEX];
+    if without(answer, &accents) == without(expected, &accents) {
+        return Mistake::AccentOnly;
+    }
+    if without(answer, &[SMOOTH, ROUGH]) == without(expected, &[SMOOTH, ROUGH]) {
+        return Mistake::BreathingOnly;
+    }
+
+    let a = bare(answer);
+    let e = bare(expected);
+    if a == e {
+        return Mistake::Diacritics;
+    }
+    if is_missing_augment(&a, &e) {
+        return Mistake::MissingAugment;
+    }
+
+    let common = a.iter().zip(&e).take_while(|(x, y)| x == y).count();
+    if common * 2 > e.len() && common + ENDING_LEN >= e.len() {
+        Mistake::WrongEnding
+    } else {
+        Mistake::WrongStem
+    }
+}
+
+// ἔλυσα typed as λυσα, or ἤκουσα typed as ἀκουσα
+fn is_missing_augment(answer: &[char], expected: &[char]) -> bool {
+    match (answer.first(), expected.first()) {
+        (_, Some('ε')) if answer == &expected[1..] => true,
+        (Some(a), Some(e)) if answer[1..] == expected[1..] => {
+            matches!((a, e), ('α', 'η') | ('ε', 'η') | ('ο', 'ω'))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lookup::form_string;
+    use hoplite_verbs_rs::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_check_answer() {
+        // oxia vs tonos, precomposed vs decomposed, final sigma, macrons, spaces
+        assert_eq!(check_answer("ἔλυσα", "ἔλῡσα"), Ok(()));
+        assert_eq!(check_answer("λυ\u{1f79}μεθα", "λυ\u{03cc}μεθα"), Ok(()));
+        assert_eq!(check_answer("λυ\u{0301}ομεθα", "λύομεθα"), Ok(()));
+        assert_eq!(check_answer(" λύεισ ", "λύεις"), Ok(()));
+        assert_eq!(check_answer("ΛΎΕΙΣ", "λύεις"), Ok(()));
+
+        // alternatives and movable ν
+        assert_eq!(check_answer("ἐλύθη", "ἐλύθην, ἐλύθη"), Ok(()));
+        assert_eq!(check_answer("ἔλυσε", "ἔλυσε(ν)"), Ok(()));
+        assert_eq!(check_answer("ἔλυσεν", "ἔλυσε(ν)"), Ok(()));
+        assert_eq!(check_answer("λύουσι", "λύουσιν"), Ok(()));
+        assert_eq!(check_answer("λύομε", "λύομεν"), Err(Mistake::WrongEnding));
+        assert_eq!(check_answer("ἔλυσε", "ἔλυσεν"), Ok(()));
+        assert_eq!(check_answer("λέλυκεν", "λέλυκε"), Ok(()));
+        assert_eq!(check_answer("λύουσιν", "λύουσι"), Ok(()));
+        assert_eq!(check_answer("λῦεν", "λῦε"), Err(Mistake::WrongEnding));
+        assert_eq!(check_answer("λυθέ", "λυθέν"), Err(Mistake::WrongEnding));
+
+        assert_eq!(check_answer("λυόμεθα", "λυόμεθα"), Ok(()));
+        assert_eq!(check_answer("λύομεθά", "λυόμεθα"), Err(Mistake::AccentOnly));
+        assert_eq!(check_answer("ἕλυσα", "ἔλυσα"), Err(Mistake::BreathingOnly));
+        assert_eq!(check_answer("ἑλύσα", "ἔλυσα"), Err(Mistake::Diacritics));
+        assert_eq!(check_answer("λῦσα", "ἔλυσα"), Err(Mistake::MissingAugment));
+        assert_eq!(
+            check_answer("ἀκουσα", "ἤκουσα"),
+            Err(Mistake::MissingAugment)
+        );
+        assert_eq!(
+            check_answer("λέλυκατε", "λελύκαμεν"),
+            Err(Mistake::WrongEnding)
+        );
+        assert_eq!(check_answer("ἔπαυσα", "ἔλυσα"), Err(Mistake::WrongStem));
+        assert_eq!(check_answer("", "ἔλυσα"), Err(Mistake::WrongStem));
+
+        assert!(same_form("λύσει, λύσῃ", "λύσει"));
+        assert!(same_form("λύσῃ", "λύσει, λύσῃ"));
+        assert!(same_form("ἔλυσεν", "ἔλυσε(ν)"));
+        assert!(!same_form("λύσει, λύσῃ", "λύσεις"));
+    }
+
+    #[test]
+    fn test_movable_nu_in_forms() {
+        let luw = "λύω, λύσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let form = |person, number, tense| {
+            let vf = HcGreekVerbForm {
+                verb: verb.clone(),
+                person: Some(person),
+                number: Some(number),
+                tense,
+                voice: HcVoice::Active,
+                mood: HcMood::Indicative,
+                gender: None,
+                case: None,
+            };
+            form_string(&vf).unwrap()
+        };
+
+        let aorist = form(HcPerson::Third, HcNumber::Singular, HcTense::Aorist);
+        assert_eq!(check_answer("ἔλυσεν", &aorist), Ok(()));
+        assert_eq!(check_answer("ἔλυσε", &aorist), Ok(()));
+        let present = form(HcPerson::Third, HcNumber::Plural, HcTense::Present);
+        assert_eq!(check_answer("λύουσιν", &present), Ok(()));
+        assert_eq!(check_answer("λύουσι", &present), Ok(()));
+        let perfect = form(HcPerson::Third, HcNumber::Plural, HcTense::Perfect);
+        assert_eq!(check_answer("λελύκασιν", &perfect), Ok(()));
+        assert_eq!(check_answer("λελύκασι", &perfect), Ok(()));
+        let plural = form(HcPerson::First, HcNumber::Plural, HcTense::Present);
+        assert_eq!(check_answer("λύομεν", &plural), Ok(()));
+        assert_eq!(check_answer("λύομε", &plural), Err(Mistake::WrongEnding));
+    }
+}
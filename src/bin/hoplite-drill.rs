@@ -0,0 +1,471 @@
+use hoplite_verb_chooser::answer::{check_answer, Mistake};
+use hoplite_verb_chooser::derivation::DerivationQuiz;
+use hoplite_verb_chooser::lookup::{describe_confusion, form_string};
+use hoplite_verb_chooser::names::{describe_changes, describe_form, ParamName};
+use hoplite_verb_chooser::parsing::{next_parsing_question, Parse};
+use hoplite_verb_chooser::verbs::{PoolVerb, VerbChooser};
+use hoplite_verb_chooser::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::sync::Arc;
+
+// principal parts, properties, hq unit
+const VERBS: &[(&str, u32, u32)] = &[
+    (
+        "παιδεύω, παιδεύσω, ἐπαίδευσα, πεπαίδευκα, πεπαίδευμαι, ἐπαιδεύθην",
+        REGULAR,
+        2,
+    ),
+    (
+        "πέμπω, πέμψω, ἔπεμψα, πέπομφα, πέπεμμαι, ἐπέμφθην",
+        CONSONANT_STEM_PERFECT_PI,
+        2,
+    ),
+    ("λύω, λύσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην", REGULAR, 3),
+    (
+        "κελεύω, κελεύσω, ἐκέλευσα, κεκέλευκα, κεκέλευσμαι, ἐκελεύσθην",
+        REGULAR,
+        3,
+    ),
+    ("θύω, θύσω, ἔθυσα, τέθυκα, τέθυμαι, ἐτύθην", REGULAR, 4),
+];
+
+const USAGE: &str = "usage: hoplite-drill [options]
+  --verb <principal parts>  drill this verb instead of one from the built-in list
+  --verb-unit <n>           hq unit of a --verb that isn't in the built-in list (default: 0)
+  --unit <n>                highest unit taught (default: all)
+  --changes <n>             number of params to change each time (default: 2)
+  --tenses <list>           e.g. present,aorist (default: all)
+  --moods <list>            e.g. indicative,infinitive,participle (default: all finite moods)
+  --voices <list>           e.g. active,middle (default: all)
+  --seed <n>                seed for a repeatable drill
+  --parse                   show forms and ask for their parameters instead
+  --derive                  build each form step by step instead";
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    verb: Option<String>,
+    verb_unit: u32,
+    unit: Option<i16>,
+    changes: u8,
+    tenses: Vec<HcTense>,
+    moods: Vec<HcMood>,
+    voices: Vec<HcVoice>,
+    seed: Option<u64>,
+    parse: bool,
+    derive: bool,
+}
+
+fn parse_list<T: ParamName>(value: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|n| T::from_name(n.trim()).ok_or_else(|| format!("unknown value: {}", n.trim())))
+        .collect()
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let mut parsed = Args {
+        verb: None,
+        verb_unit: 0,
+        unit: None,
+        changes: 2,
+        tenses: vec![
+            HcTense::Present,
+            HcTense::Imperfect,
+            HcTense::Future,
+            HcTense::Aorist,
+            HcTense::Perfect,
+            HcTense::Pluperfect,
+        ],
+        moods: vec![
+            HcMood::Indicative,
+            HcMood::Subjunctive,
+            HcMood::Optative,
+            HcMood::Imperative,
+        ],
+        voices: vec![HcVoice::Active, HcVoice::Middle, HcVoice::Passive],
+        seed: None,
+        parse: false,
+        derive: false,
+    };
+
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "-h" | "--help" => return Err(String::new()),
+            "--parse" => {
+                parsed.parse = true;
+                continue;
+            }
+            "--derive" => {
+                parsed.derive = true;
+                continue;
+            }
+            _ => args
+                .next()
+                .ok_or_else(|| format!("missing value for {}", arg))?,
+        };
+        match arg.as_str() {
+            "--verb" => parsed.verb = Some(value),
+            "--verb-unit" => {
+                parsed.verb_unit = value.parse().map_err(|_| format!("bad unit: {}", value))?
+            }
+            "--unit" => {
+                parsed.unit = Some(value.parse().map_err(|_| format!("bad unit: {}", value))?)
+            }
+            "--changes" => {
+                parsed.changes = value
+                    .parse()
+                    .map_err(|_| format!("bad number of changes: {}", value))?
+            }
+            "--tenses" => parsed.tenses = parse_list(&value)?,
+            "--moods" => parsed.moods = parse_list(&value)?,
+            "--voices" => parsed.voices = parse_list(&value)?,
+            "--seed" => {
+                parsed.seed = Some(value.parse().map_err(|_| format!("bad seed: {}", value))?)
+            }
+            _ => return Err(format!("unknown option: {}", arg)),
+        }
+    }
+    Ok(parsed)
+}
+
+// a built-in verb with the same first principal part keeps its properties and unit; any
+// other verb is taken to be regular
+fn load_verb(pps: &str, unit: u32) -> Option<HcGreekVerb> {
+    let first = |pps: &str| pps.split(',').next().unwrap_or("").trim().to_string();
+    let (properties, unit) = VERBS
+        .iter()
+        .find(|(known, _, _)| first(known) == first(pps))
+        .map_or((REGULAR, unit), |(_, properties, unit)| {
+            (*properties, *unit)
+        });
+    HcGreekVerb::from_string(1, pps, properties, unit)
+}
+
+fn parse_drill(
+    mut prev: HcGreekVerbForm,
+    args: &Args,
+    parameters: &VerbParameters,
+    options: &RandomFormOptions,
+    rng: &mut StdRng,
+) {
+    println!("type person, number, tense, voice and mood, e.g. first singular present active indicative, or q to quit");
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut asked = 0;
+    let mut right = 0;
+    loop {
+        let question = match next_parsing_question(
+            &prev,
+            args.changes,
+            args.unit,
+            parameters,
+            None,
+            options,
+            rng,
+        ) {
+            Ok(question) => question,
+            Err(e) => {
+                eprintln!("{}", e);
+                break;
+            }
+        };
+
+        println!();
+        println!("{}", question.greek);
+        let parse = loop {
+            print!("> ");
+            io::stdout().flush().unwrap();
+            let Some(Ok(line)) = lines.next() else {
+                break None;
+            };
+            if line.trim() == "q" {
+                break None;
+            }
+            match Parse::from_words(&line) {
+                Ok(parse) => break Some(parse),
+                Err(e) => println!("{}", e),
+            }
+        };
+        let Some(parse) = parse else {
+            break;
+        };
+
+        asked += 1;
+        let answers = question
+            .accepted
+            .iter()
+            .map(describe_form)
+            .collect::<Vec<_>>()
+            .join(" or ");
+        if question.check(&parse) {
+            right += 1;
+            println!("correct: {}", answers);
+        } else {
+            println!("incorrect: {}", answers);
+        }
+        prev = question.form;
+    }
+    println!();
+    println!("{} of {} correct", right, asked);
+}
+
+fn derive_drill(
+    mut prev: HcGreekVerbForm,
+    args: &Args,
+    parameters: &VerbParameters,
+    options: &RandomFormOptions,
+    rng: &mut StdRng,
+) {
+    println!("type the form after each step, or q to quit");
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut asked = 0;
+    let mut right = 0;
+    'forms: loop {
+        let next = match prev.random_form_with_options(
+            args.changes,
+            args.unit,
+            parameters,
+            None,
+            options,
+            rng,
+        ) {
+            Ok((next, _, _)) => next,
+            Err(e) => {
+                eprintln!("{}", e);
+                break;
+            }
+        };
+        let Ok(mut quiz) = DerivationQuiz::new(next.clone()) else {
+            prev = next;
+            continue;
+        };
+
+        println!();
+        println!("{}", describe_form(&next));
+        while let Some(stage) = quiz.current() {
+            println!("{} → {}", quiz.previous_form(), stage.explanation);
+            let form = stage.form.clone();
+            print!("> ");
+            io::stdout().flush().unwrap();
+            let Some(Ok(line)) = lines.next() else {
+                break 'forms;
+            };
+            if line.trim() == "q" {
+                break 'forms;
+            }
+            asked += 1;
+            match quiz.answer(&line) {
+                Some(true) => {
+                    right += 1;
+                    println!("correct");
+                }
+                Some(false) => println!("incorrect: {}", form),
+                None => (),
+            }
+        }
+        let (stages_right, stages) = quiz.score();
+        println!("{} of {} steps correct", stages_right, stages);
+        prev = next;
+    }
+    println!();
+    println!("{} of {} steps correct", right, asked);
+}
+
+fn main() {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("{}", e);
+            }
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let verb = match &args.verb {
+        Some(pps) => load_verb(pps, args.verb_unit).map(Arc::new),
+        None => VerbChooser::new(
+            VERBS
+                .iter()
+                .enumerate()
+                .filter_map(|(id, (pps, properties, unit))| {
+                    HcGreekVerb::from_string(id as u32 + 1, pps, *properties, *unit)
+                })
+                .map(|verb| PoolVerb::new(Arc::new(verb)))
+                .collect(),
+        )
+        .next_verb_with_rng(args.unit, &mut rng),
+    };
+    let Some(verb) = verb else {
+        eprintln!("could not load a verb");
+        process::exit(1);
+    };
+
+    let parameters = VerbParameters {
+        persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+        numbers: vec![HcNumber::Singular, HcNumber::Plural],
+        tenses: args.tenses.clone(),
+        voices: args.voices.clone(),
+        moods: args.moods.clone(),
+    };
+    let options = RandomFormOptions::default();
+
+    // the first form, in the order the params were given, that the verb has in the unit
+    let start = allowed_forms(&verb, args.unit, &parameters, &options)
+        .into_iter()
+        .next();
+    let Some(mut prev) = start else {
+        eprintln!("the verb has no form with the given tenses, voices and moods in the unit");
+        process::exit(1);
+    };
+
+    if args.parse {
+        parse_drill(prev, &args, &parameters, &options, &mut rng);
+        return;
+    }
+    if args.derive {
+        derive_drill(prev, &args, &parameters, &options, &mut rng);
+        return;
+    }
+
+    println!("type the requested form, or q to quit");
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut asked = 0;
+    let mut right = 0;
+    let mut accent_only = 0;
+    loop {
+        let next = match prev.random_form_with_options(
+            args.changes,
+            args.unit,
+            &parameters,
+            None,
+            &options,
+            &mut rng,
+        ) {
+            Ok((next, _, _)) => next,
+            Err(e) => {
+                eprintln!("{}", e);
+                break;
+            }
+        };
+        let Some(answer) = form_string(&next) else {
+            prev = next;
+            continue;
+        };
+
+        println!();
+        println!(
+            "{} ({})",
+            form_string(&prev).unwrap_or_else(|| "—".to_string()),
+            describe_form(&prev)
+        );
+        println!("change {}", describe_changes(&prev, &next).join(", "));
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        if line.trim() == "q" {
+            break;
+        }
+        asked += 1;
+        match check_answer(&line, &answer) {
+            Ok(()) => {
+                right += 1;
+                println!("correct");
+            }
+            Err(Mistake::AccentOnly) => {
+                accent_only += 1;
+                println!("almost: {} ({})", answer, Mistake::AccentOnly);
+            }
+            Err(mistake) => match describe_confusion(&next, &line) {
+                Some(confusion) => println!("incorrect: {} ({})", answer, confusion),
+                None => println!("incorrect: {} ({})", answer, mistake),
+            },
+        }
+        prev = next;
+    }
+    println!();
+    println!(
+        "{} of {} correct, {} with only the accent wrong",
+        right, asked, accent_only
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse_args(
+            ["--unit", "5", "--tenses", "present,aorist", "--seed", "7"]
+                .iter()
+                .map(|s| s.to_string()),
+        )
+        .unwrap();
+        assert_eq!(args.unit, Some(5));
+        assert_eq!(args.changes, 2);
+        assert_eq!(args.tenses, vec![HcTense::Present, HcTense::Aorist]);
+        assert_eq!(args.seed, Some(7));
+        assert!(!args.parse);
+        assert!(
+            parse_args(["--parse"].iter().map(|s| s.to_string()))
+                .unwrap()
+                .parse
+        );
+
+        assert!(parse_args(
+            ["--moods", "indicative,middle"]
+                .iter()
+                .map(|s| s.to_string())
+        )
+        .is_err());
+        assert!(parse_args(["--unit"].iter().map(|s| s.to_string())).is_err());
+    }
+
+    #[test]
+    fn test_load_verb_and_start_form() {
+        let verb = load_verb("πέμπω, πέμψω, ἔπεμψα, πέπομφα, πέπεμμαι, ἐπέμφθην", 0).unwrap();
+        assert_eq!(
+            (verb.properties, verb.hq_unit),
+            (CONSONANT_STEM_PERFECT_PI, 2)
+        );
+        let verb = load_verb("ἄγω, ἄξω, ἤγαγον, ἦχα, ἦγμαι, ἤχθην", 5).unwrap();
+        assert_eq!((verb.properties, verb.hq_unit), (REGULAR, 5));
+
+        let parameters = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Aorist],
+            voices: vec![HcVoice::Middle],
+            moods: vec![HcMood::Imperative, HcMood::Indicative],
+        };
+        let options = RandomFormOptions::default();
+        let form = allowed_forms(&Arc::new(verb), None, &parameters, &options)
+            .into_iter()
+            .next()
+            .unwrap();
+        assert_eq!(
+            (form.person, form.tense, form.voice, form.mood),
+            (
+                Some(HcPerson::Second),
+                HcTense::Aorist,
+                HcVoice::Middle,
+                HcMood::Imperative
+            )
+        );
+    }
+}
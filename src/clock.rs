@@ -0,0 +1,36 @@
+use std::time::{Duration, Instant};
+
+/// Time source for turn limits and session timestamps, so front ends and tests can drive time.
+pub trait Clock {
+    /// Time since some fixed start.
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// a clock the test moves by setting the shared cell
+#[cfg(test)]
+pub(crate) struct TestClock(pub std::rc::Rc<std::cell::Cell<Duration>>);
+
+#[cfg(test)]
+impl Clock for TestClock {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+}
@@ -0,0 +1,525 @@
+use crate::names::{deserialize_names, ParamName};
+use crate::verbs;
+use hoplite_verbs_rs::*;
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Groups of verbs that are introduced separately from the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerbClass {
+    MiVerb,
+    Isthmi,
+    ConsonantStem,
+}
+
+impl VerbClass {
+    pub fn matches(&self, vf: &HcGreekVerbForm) -> bool {
+        match self {
+            VerbClass::MiVerb => vf.verb.pps[0].ends_with("μι"),
+            VerbClass::Isthmi => vf.verb.pps[0].ends_with("στημι"),
+            VerbClass::ConsonantStem => vf.is_consonant_stem(""),
+        }
+    }
+}
+
+/// Why a form is not asked at a unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockReason {
+    #[serde(skip_deserializing)]
+    VoiceNotYetTaught(HcVoice),
+    #[serde(skip_deserializing)]
+    MoodNotYetTaught(HcMood),
+    #[serde(skip_deserializing)]
+    TenseNotYetTaught(HcTense),
+    ImperativeNotYetTaught,
+    MiVerbNotYetTaught,
+    MiVerbAorist,
+    IsthmiPerfect,
+    FutureOptative,
+    #[serde(rename = "consonant_stem_third_plural_perfect_mp")]
+    ConsonantStemThirdPluralPerfectMP,
+    /// The verb's hq unit is after the current unit.
+    #[serde(skip_deserializing)]
+    VerbNotYetTaught,
+    /// The principal part (1 to 6) the form is made from is introduced after the current unit.
+    #[serde(skip_deserializing)]
+    PrincipalPartNotYetTaught(u8),
+    /// Excluded by a rule that gives no reason.
+    Excluded,
+}
+
+impl fmt::Display for BlockReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockReason::VoiceNotYetTaught(v) => {
+                write!(f, "the {} voice has not been taught yet", v.name())
+            }
+            BlockReason::MoodNotYetTaught(m) => {
+                write!(f, "the {} has not been taught yet", m.name())
+            }
+            BlockReason::TenseNotYetTaught(t) => {
+                write!(f, "the {} tense has not been taught yet", t.name())
+            }
+            BlockReason::ImperativeNotYetTaught => {
+                write!(f, "the imperative has not been taught yet")
+            }
+            BlockReason::MiVerbNotYetTaught => write!(f, "μι verbs have not been taught yet"),
+            BlockReason::MiVerbAorist => {
+                write!(f, "the aorist of μι verbs has not been taught yet")
+            }
+            BlockReason::IsthmiPerfect => write!(
+                f,
+                "the aorist, perfect and pluperfect of ἵστημι have not been taught yet"
+            ),
+            BlockReason::FutureOptative => {
+                write!(f, "the future optative has not been taught yet")
+            }
+            BlockReason::ConsonantStemThirdPluralPerfectMP => write!(
+                f,
+                "the third person plural perfect and pluperfect middle/passive of consonant stems has not been taught yet"
+            ),
+            BlockReason::VerbNotYetTaught => write!(f, "this verb has not been taught yet"),
+            BlockReason::PrincipalPartNotYetTaught(pp) => write!(
+                f,
+                "the {} principal part of this verb has not been taught yet",
+                ["first", "second", "third", "fourth", "fifth", "sixth"]
+                    .get(usize::from(*pp).wrapping_sub(1))
+                    .unwrap_or(&"unknown")
+            ),
+            BlockReason::Excluded => write!(f, "this form is not asked at this unit"),
+        }
+    }
+}
+
+/// A set of form cells. Every field that is given must match; a missing field matches anything.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CellPattern {
+    /// Shown to students when this pattern blocks a form; see [`CellPattern::reason_for`].
+    pub reason: Option<BlockReason>,
+    pub verb_class: Option<VerbClass>,
+    #[serde(deserialize_with = "deserialize_names")]
+    pub persons: Option<Vec<HcPerson>>,
+    #[serde(deserialize_with = "deserialize_names")]
+    pub numbers: Option<Vec<HcNumber>>,
+    #[serde(deserialize_with = "deserialize_names")]
+    pub tenses: Option<Vec<HcTense>>,
+    #[serde(deserialize_with = "deserialize_names")]
+    pub moods: Option<Vec<HcMood>>,
+    #[serde(deserialize_with = "deserialize_names")]
+    pub voices: Option<Vec<HcVoice>>,
+}
+
+impl CellPattern {
+    pub fn matches(&self, vf: &HcGreekVerbForm) -> bool {
+        self.verb_class.is_none_or(|c| c.matches(vf))
+            && (self.persons.is_none() || vf.person.is_some_and(|p| in_list(&self.persons, &p)))
+            && (self.numbers.is_none() || vf.number.is_some_and(|n| in_list(&self.numbers, &n)))
+            && in_list(&self.tenses, &vf.tense)
+            && in_list(&self.moods, &vf.mood)
+            && in_list(&self.voices, &vf.voice)
+    }
+
+    // without an explicit reason, a pattern on a single parameter explains itself
+    pub fn reason_for(&self, vf: &HcGreekVerbForm) -> BlockReason {
+        if let Some(reason) = self.reason {
+            return reason;
+        }
+        let only_voices = self.voices.is_some() && self.moods.is_none() && self.tenses.is_none();
+        let only_moods = self.moods.is_some() && self.voices.is_none() && self.tenses.is_none();
+        let only_tenses = self.tenses.is_some() && self.voices.is_none() && self.moods.is_none();
+        if self.verb_class.is_some() || self.persons.is_some() || self.numbers.is_some() {
+            BlockReason::Excluded
+        } else if only_voices {
+            BlockReason::VoiceNotYetTaught(vf.voice)
+        } else if only_moods {
+            mood_reason(vf.mood)
+        } else if only_tenses {
+            BlockReason::TenseNotYetTaught(vf.tense)
+        } else {
+            BlockReason::Excluded
+        }
+    }
+}
+
+// which principal part (1 to 6) the form is made from
+pub fn principal_part(vf: &HcGreekVerbForm) -> u8 {
+    principal_part_for(&vf.verb, vf.tense, vf.voice)
+}
+
+// which principal part (1 to 6) the verb makes the tense and voice from
+// a perfect used as a present, like οἶδα, makes its perfect active from the first
+pub fn principal_part_for(verb: &HcGreekVerb, tense: HcTense, voice: HcVoice) -> u8 {
+    match (tense, voice) {
+        (HcTense::Perfect | HcTense::Pluperfect, HcVoice::Active)
+            if verbs::is_perfect_as_present(verb) =>
+        {
+            1
+        }
+        (HcTense::Present | HcTense::Imperfect, _) => 1,
+        (HcTense::Future | HcTense::Aorist, HcVoice::Passive) => 6,
+        (HcTense::Future, _) => 2,
+        (HcTense::Aorist, _) => 3,
+        (HcTense::Perfect | HcTense::Pluperfect, HcVoice::Active) => 4,
+        (HcTense::Perfect | HcTense::Pluperfect, _) => 5,
+    }
+}
+
+fn mood_reason(mood: HcMood) -> BlockReason {
+    if mood == HcMood::Imperative {
+        BlockReason::ImperativeNotYetTaught
+    } else {
+        BlockReason::MoodNotYetTaught(mood)
+    }
+}
+
+// a missing list contains everything
+fn in_list<T: PartialEq>(list: &Option<Vec<T>>, value: &T) -> bool {
+    list.as_ref().is_none_or(|l| l.contains(value))
+}
+
+/// What may be asked up to and including `through_unit`.
+/// The `voices`, `moods` and `tenses` lists are what is allowed; a missing list allows everything.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnitRule {
+    pub through_unit: i16,
+    #[serde(default, deserialize_with = "deserialize_names")]
+    pub voices: Option<Vec<HcVoice>>,
+    #[serde(default, deserialize_with = "deserialize_names")]
+    pub moods: Option<Vec<HcMood>>,
+    #[serde(default, deserialize_with = "deserialize_names")]
+    pub tenses: Option<Vec<HcTense>>,
+    #[serde(default)]
+    pub exclude: Vec<CellPattern>,
+}
+
+impl UnitRule {
+    pub fn blocks(&self, vf: &HcGreekVerbForm) -> bool {
+        !in_list(&self.voices, &vf.voice)
+            || !in_list(&self.moods, &vf.mood)
+            || !in_list(&self.tenses, &vf.tense)
+            || self.exclude.iter().any(|c| c.matches(vf))
+    }
+
+    // every reason this rule blocks the form; empty if it is allowed
+    pub fn block_reasons(&self, vf: &HcGreekVerbForm) -> Vec<BlockReason> {
+        let mut reasons = vec![];
+        if !in_list(&self.voices, &vf.voice) {
+            reasons.push(BlockReason::VoiceNotYetTaught(vf.voice));
+        }
+        if !in_list(&self.moods, &vf.mood) {
+            reasons.push(mood_reason(vf.mood));
+        }
+        if !in_list(&self.tenses, &vf.tense) {
+            reasons.push(BlockReason::TenseNotYetTaught(vf.tense));
+        }
+        for c in self.exclude.iter().filter(|c| c.matches(vf)) {
+            let reason = c.reason_for(vf);
+            if !reasons.contains(&reason) {
+                reasons.push(reason);
+            }
+        }
+        reasons
+    }
+}
+
+/// A principal part of one verb that is introduced later than the verb itself,
+/// e.g. `{ "lemma": "λείπω", "principal_part": 6, "unit": 12 }`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrincipalPartUnit {
+    /// The verb's first principal part.
+    pub lemma: String,
+    pub principal_part: u8,
+    pub unit: i16,
+}
+
+#[derive(Debug)]
+pub enum CurriculumError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for CurriculumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CurriculumError::Io(e) => write!(f, "could not read curriculum: {}", e),
+            CurriculumError::Parse(e) => write!(f, "could not parse curriculum: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CurriculumError {}
+
+/// Unit gating rules: which forms may be asked at each unit of a syllabus.
+///
+/// A unit uses the first rule whose `through_unit` is at or above it; units past the last
+/// rule are not gated by form. At every unit, verbs whose `hq_unit` is later, and the
+/// `principal_parts` introduced later, are blocked. The built-in Hardy/Hoplite sequence
+/// is [`Curriculum::hoplite`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Curriculum {
+    pub units: Vec<UnitRule>,
+    #[serde(default)]
+    pub principal_parts: Vec<PrincipalPartUnit>,
+}
+
+impl Curriculum {
+    pub fn from_json(json: &str) -> Result<Curriculum, CurriculumError> {
+        let mut curriculum: Curriculum =
+            serde_json::from_str(json).map_err(CurriculumError::Parse)?;
+        curriculum.units.sort_by_key(|r| r.through_unit);
+        Ok(curriculum)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Curriculum, CurriculumError> {
+        let json = std::fs::read_to_string(path).map_err(CurriculumError::Io)?;
+        Curriculum::from_json(&json)
+    }
+
+    /// The Hardy/Hoplite unit sequence used by `block_for_hq_unit`.
+    pub fn hoplite() -> &'static Curriculum {
+        static HOPLITE: OnceLock<Curriculum> = OnceLock::new();
+        HOPLITE
+            .get_or_init(|| Curriculum::from_json(include_str!("hoplite_curriculum.json")).unwrap())
+    }
+
+    pub fn rule_for_unit(&self, unit: i16) -> Option<&UnitRule> {
+        self.units.iter().find(|r| unit <= r.through_unit)
+    }
+
+    // the verb or the form's principal part comes after the unit
+    fn verb_reasons(&self, vf: &HcGreekVerbForm, unit: i16) -> Vec<BlockReason> {
+        let mut reasons = vec![];
+        if i64::from(vf.verb.hq_unit) > i64::from(unit) {
+            reasons.push(BlockReason::VerbNotYetTaught);
+        }
+        let pp = principal_part(vf);
+        let lemma = vf.verb.pps.first().map_or("", |l| l.trim());
+        if self
+            .principal_parts
+            .iter()
+            .any(|p| p.lemma == lemma && p.principal_part == pp && p.unit > unit)
+        {
+            reasons.push(BlockReason::PrincipalPartNotYetTaught(pp));
+        }
+        reasons
+    }
+
+    // true to block the form, false to allow it
+    pub fn blocks(&self, vf: &HcGreekVerbForm, unit: Option<i16>) -> bool {
+        unit.is_some_and(|u| {
+            !self.verb_reasons(vf, u).is_empty()
+                || self.rule_for_unit(u).is_some_and(|r| r.blocks(vf))
+        })
+    }
+
+    pub fn block_reasons(&self, vf: &HcGreekVerbForm, unit: Option<i16>) -> Vec<BlockReason> {
+        let Some(unit) = unit else {
+            return vec![];
+        };
+        let mut reasons = self.verb_reasons(vf, unit);
+        if let Some(rule) = self.rule_for_unit(unit) {
+            reasons.extend(rule.block_reasons(vf));
+        }
+        reasons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_reasons_for_hq_unit, GetRandom};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_custom_curriculum() {
+        // middle voice before passive, and imperatives from the start
+        let json = r#"{
+            "units": [
+                { "through_unit": 8, "voices": ["active", "middle"] },
+                { "through_unit": 3, "voices": ["active"], "moods": ["indicative", "imperative"] }
+            ]
+        }"#;
+        let curriculum = Curriculum::from_json(json).unwrap();
+        assert_eq!(curriculum.units[0].through_unit, 3);
+
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let vf = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::Second),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Imperative,
+            gender: None,
+            case: None,
+        };
+        assert!(!curriculum.blocks(&vf, Some(2)));
+        assert!(vf.block_for_hq_unit(Some(2)));
+
+        let vf = HcGreekVerbForm {
+            voice: HcVoice::Middle,
+            mood: HcMood::Indicative,
+            ..vf
+        };
+        assert!(curriculum.blocks(&vf, Some(3)));
+        assert!(!curriculum.blocks(&vf, Some(4)));
+
+        let vf = HcGreekVerbForm {
+            voice: HcVoice::Passive,
+            ..vf
+        };
+        assert!(curriculum.blocks(&vf, Some(8)));
+        assert!(!curriculum.blocks(&vf, Some(9)));
+        assert!(!curriculum.blocks(&vf, None));
+    }
+
+    #[test]
+    fn test_block_reasons() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let vf = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Future,
+            voice: HcVoice::Active,
+            mood: HcMood::Optative,
+            gender: None,
+            case: None,
+        };
+        assert_eq!(
+            block_reasons_for_hq_unit(&vf, Some(4)),
+            vec![BlockReason::FutureOptative]
+        );
+        assert_eq!(
+            block_reasons_for_hq_unit(&vf, Some(2)),
+            vec![BlockReason::MoodNotYetTaught(HcMood::Optative)]
+        );
+        assert!(block_reasons_for_hq_unit(&vf, Some(16)).is_empty());
+        assert!(block_reasons_for_hq_unit(&vf, None).is_empty());
+        assert_eq!(
+            BlockReason::FutureOptative.to_string(),
+            "the future optative has not been taught yet"
+        );
+
+        let vf = HcGreekVerbForm {
+            person: Some(HcPerson::Second),
+            tense: HcTense::Present,
+            voice: HcVoice::Middle,
+            mood: HcMood::Imperative,
+            ..vf
+        };
+        assert_eq!(
+            block_reasons_for_hq_unit(&vf, Some(4)),
+            vec![
+                BlockReason::VoiceNotYetTaught(HcVoice::Middle),
+                BlockReason::ImperativeNotYetTaught
+            ]
+        );
+        assert_eq!(
+            BlockReason::VoiceNotYetTaught(HcVoice::Middle).to_string(),
+            "the middle voice has not been taught yet"
+        );
+
+        let vf = HcGreekVerbForm {
+            tense: HcTense::Perfect,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            ..vf
+        };
+        assert_eq!(
+            block_reasons_for_hq_unit(&vf, Some(2)),
+            vec![BlockReason::TenseNotYetTaught(HcTense::Perfect)]
+        );
+        // reasons agree with block_for_hq_unit at every unit
+        for unit in 1..=20 {
+            assert_eq!(
+                vf.block_for_hq_unit(Some(unit)),
+                !block_reasons_for_hq_unit(&vf, Some(unit)).is_empty()
+            );
+        }
+    }
+
+    #[test]
+    fn test_verb_and_principal_part_units() {
+        let json = r#"{
+            "units": [ { "through_unit": 2, "voices": ["active"] } ],
+            "principal_parts": [ { "lemma": "λείπω", "principal_part": 6, "unit": 12 } ]
+        }"#;
+        let curriculum = Curriculum::from_json(json).unwrap();
+
+        let leipw = "λείπω, λείψω, ἔλιπον, λέλοιπα, λέλειμμαι, ἐλείφθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, leipw, REGULAR, 5).unwrap());
+        let vf = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Aorist,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        assert_eq!(
+            curriculum.block_reasons(&vf, Some(4)),
+            vec![BlockReason::VerbNotYetTaught]
+        );
+        assert!(!curriculum.blocks(&vf, Some(5)));
+        assert!(!curriculum.blocks(&vf, None));
+        // the built-in curriculum gates verbs too
+        assert!(vf.block_for_hq_unit(Some(4)));
+        assert!(!vf.block_for_hq_unit(Some(5)));
+
+        let vf = HcGreekVerbForm {
+            voice: HcVoice::Passive,
+            ..vf
+        };
+        assert_eq!(principal_part(&vf), 6);
+        assert_eq!(
+            curriculum.block_reasons(&vf, Some(11)),
+            vec![BlockReason::PrincipalPartNotYetTaught(6)]
+        );
+        assert!(!curriculum.blocks(&vf, Some(12)));
+        assert_eq!(
+            BlockReason::PrincipalPartNotYetTaught(6).to_string(),
+            "the sixth principal part of this verb has not been taught yet"
+        );
+
+        // the future passive is made from the sixth principal part too
+        let vf = HcGreekVerbForm {
+            tense: HcTense::Future,
+            ..vf
+        };
+        assert!(curriculum.blocks(&vf, Some(11)));
+        let vf = HcGreekVerbForm {
+            voice: HcVoice::Middle,
+            ..vf
+        };
+        assert!(!curriculum.blocks(&vf, Some(11)));
+    }
+
+    #[test]
+    fn test_bad_curriculum() {
+        assert!(matches!(
+            Curriculum::from_json(
+                r#"{ "units": [ { "through_unit": 2, "voices": ["medium"] } ] }"#
+            ),
+            Err(CurriculumError::Parse(_))
+        ));
+        assert!(matches!(
+            Curriculum::from_json(
+                r#"{ "units": [ { "through_unit": 2, "exclude": [ { "verb_class": "contract" } ] } ] }"#
+            ),
+            Err(CurriculumError::Parse(_))
+        ));
+    }
+}
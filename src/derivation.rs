@@ -0,0 +1,129 @@
+use crate::answer::normalize;
+use hoplite_verbs_rs::*;
+
+/// One step of `get_form` to be built by the student, e.g. adding the augment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationStage {
+    pub explanation: String,
+    pub form: String,
+}
+
+/// Quizzes the steps of `get_form` in turn: starting from the first step, the student types
+/// the form after each later step and every stage is graded on its own. Stages are only
+/// right or wrong: intermediate forms aren't words, so `check_answer`'s mistakes don't fit.
+#[derive(Debug, Clone)]
+pub struct DerivationQuiz {
+    pub target: HcGreekVerbForm,
+    pub start: String,
+    stages: Vec<DerivationStage>,
+    results: Vec<bool>,
+}
+
+impl DerivationQuiz {
+    pub fn new(target: HcGreekVerbForm) -> Result<DerivationQuiz, HcFormError> {
+        let steps = target.get_form(false)?;
+        let mut steps = steps.into_iter().map(|s| DerivationStage {
+            explanation: s.explanation,
+            form: s.form,
+        });
+        let start = steps.next().map(|s| s.form).unwrap_or_default();
+        Ok(DerivationQuiz {
+            target,
+            start,
+            stages: steps.collect(),
+            results: vec![],
+        })
+    }
+
+    pub fn stages(&self) -> &[DerivationStage] {
+        &self.stages
+    }
+
+    // the stage to answer next, None when finished
+    pub fn current(&self) -> Option<&DerivationStage> {
+        self.stages.get(self.results.len())
+    }
+
+    // the form the current stage starts from: the right form of the stage before
+    pub fn previous_form(&self) -> &str {
+        match self.results.len() {
+            0 => &self.start,
+            n => &self.stages[n - 1].form,
+        }
+    }
+
+    /// Grades the answer for the current stage and moves on to the next one,
+    /// whether or not it was right. None if the quiz is finished. The answer must match the
+    /// stage's form once both are normalized; no movable ν or other variants are accepted.
+    pub fn answer(&mut self, answer: &str) -> Option<bool> {
+        let right = normalize(answer) == normalize(&self.current()?.form);
+        self.results.push(right);
+        Some(right)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current().is_none()
+    }
+
+    pub fn results(&self) -> &[bool] {
+        &self.results
+    }
+
+    // right answers and stages answered
+    pub fn score(&self) -> (usize, usize) {
+        (
+            self.results.iter().filter(|r| **r).count(),
+            self.results.len(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_derivation_quiz() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let vf = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::Third),
+            number: Some(HcNumber::Plural),
+            tense: HcTense::Aorist,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let steps = vf.get_form(false).unwrap();
+
+        let mut quiz = DerivationQuiz::new(vf.clone()).unwrap();
+        assert_eq!(quiz.start, steps[0].form);
+        assert_eq!(quiz.stages().len(), steps.len() - 1);
+        assert_eq!(quiz.previous_form(), steps[0].form);
+
+        let mut right = 0;
+        for (i, step) in steps.iter().enumerate().skip(1) {
+            assert_eq!(quiz.current().unwrap().explanation, step.explanation);
+            if i % 2 == 1 {
+                assert_eq!(quiz.answer(&step.form), Some(true));
+                right += 1;
+            } else {
+                assert_eq!(quiz.answer("οὐδέν"), Some(false));
+            }
+            assert_eq!(quiz.previous_form(), step.form);
+        }
+        assert!(quiz.is_finished());
+        assert_eq!(quiz.answer(&steps[0].form), None);
+        assert_eq!(quiz.score(), (right, steps.len() - 1));
+
+        let bad = HcGreekVerbForm {
+            person: Some(HcPerson::First),
+            mood: HcMood::Imperative,
+            ..vf
+        };
+        assert!(DerivationQuiz::new(bad).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::answer::same_form;
+use crate::lookup::form_string;
+use crate::{alternative_values, FormParam, RandomFormOptions};
+use hoplite_verbs_rs::*;
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use std::fmt;
+use unicode_normalization::UnicodeNormalization;
+
+const ACUTE: char = '\u{0301}';
+const ACCENTS: [char; 3] = ['\u{0300}', ACUTE, '\u{0342}'];
+const VOWELS: [char; 7] = ['α', 'ε', 'η', 'ι', 'ο', 'υ', 'ω'];
+
+/// What a distractor plays on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistractorKind {
+    /// The form of a cell one change of this param away.
+    Param(FormParam),
+    /// The right form with the accent moved.
+    Accent,
+    /// The right form without its augment.
+    Augment,
+}
+
+impl fmt::Display for DistractorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DistractorKind::Param(FormParam::Param(HcParameters::Person)) => write!(f, "person"),
+            DistractorKind::Param(FormParam::Param(HcParameters::Number)) => write!(f, "number"),
+            DistractorKind::Param(FormParam::Param(HcParameters::Tense)) => write!(f, "tense"),
+            DistractorKind::Param(FormParam::Param(HcParameters::Mood)) => write!(f, "mood"),
+            DistractorKind::Param(FormParam::Param(HcParameters::Voice)) => write!(f, "voice"),
+            DistractorKind::Param(FormParam::Declension) => write!(f, "declension"),
+            DistractorKind::Accent => write!(f, "accent"),
+            DistractorKind::Augment => write!(f, "augment"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distractor {
+    pub form: String,
+    pub kind: DistractorKind,
+    /// The cell the form belongs to, for `DistractorKind::Param`.
+    pub cell: Option<HcGreekVerbForm>,
+}
+
+// the form with its accent moved to each other vowel, as an acute
+fn accent_variants(form: &str) -> Vec<String> {
+    let decomposed: Vec<char> = form.nfd().collect();
+    let Some(accent_at) = decomposed.iter().position(|c| ACCENTS.contains(c)) else {
+        return vec![];
+    };
+    let mut unaccented = decomposed.clone();
+    unaccented.remove(accent_at);
+
+    let mut variants = vec![];
+    for (i, c) in unaccented.iter().enumerate() {
+        if !VOWELS.contains(c) {
+            continue;
+        }
+        // after the vowel's other marks, e.g. its breathing
+        let mut at = i + 1;
+        while at < unaccented.len() && ('\u{0300}'..='\u{036f}').contains(&unaccented[at]) {
+            at += 1;
+        }
+        if at == accent_at {
+            continue;
+        }
+        let mut variant = unaccented.clone();
+        variant.insert(at, ACUTE);
+        variants.push(variant.into_iter().nfc().collect());
+    }
+    variants
+}
+
+// ἔλυσα as λυσα; None if the form doesn't start with a syllabic augment
+fn without_augment(form: &str) -> Option<String> {
+    let decomposed: Vec<char> = form.nfd().collect();
+    if decomposed.first() != Some(&'ε') {
+        return None;
+    }
+    let rest: Vec<char> = decomposed[1..]
+        .iter()
+        .copied()
+        .skip_while(|c| ('\u{0300}'..='\u{036f}').contains(c))
+        .collect();
+    // a vowel after the augment would need a breathing and contraction
+    if rest.first().is_none_or(|c| VOWELS.contains(c)) {
+        return None;
+    }
+    Some(rest.into_iter().nfc().collect())
+}
+
+/// Up to k believable wrong answers for target: forms of cells one param change away,
+/// chosen from the values in parameters as `change_params` would, and the right form with
+/// its accent moved or augment dropped, for each of its spellings. None of them shares a
+/// spelling with the right form, and no two share one.
+pub fn distractors<R: Rng + ?Sized>(
+    target: &HcGreekVerbForm,
+    k: usize,
+    parameters: &VerbParameters,
+    rng: &mut R,
+) -> Vec<Distractor> {
+    let Some(correct) = form_string(target) else {
+        return vec![];
+    };
+
+    let mut candidates = vec![];
+    // no alternatives for the params the target's mood doesn't have
+    for p in FormParam::ALL {
+        for cell in alternative_values(target, &p, parameters, &RandomFormOptions::default()) {
+            if let Some(form) = form_string(&cell) {
+                candidates.push(Distractor {
+                    form,
+                    kind: DistractorKind::Param(p),
+                    cell: Some(cell),
+                });
+            }
+        }
+    }
+    for alt in correct.split(',').map(str::trim) {
+        for form in accent_variants(alt) {
+            candidates.push(Distractor {
+                form,
+                kind: DistractorKind::Accent,
+                cell: None,
+            });
+        }
+        if let Some(form) = without_augment(alt) {
+            candidates.push(Distractor {
+                form,
+                kind: DistractorKind::Augment,
+                cell: None,
+            });
+        }
+    }
+
+    candidates.shuffle(rng);
+    let mut chosen: Vec<Distractor> = vec![];
+    for d in candidates {
+        if chosen.len() == k {
+            break;
+        }
+        if !same_form(&d.form, &correct) && !chosen.iter().any(|c| same_form(&c.form, &d.form)) {
+            chosen.push(d);
+        }
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::names::describe_changes;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_accent_and_augment_variants() {
+        assert_eq!(accent_variants("ἔλυσα"), vec!["ἐλύσα", "ἐλυσά"]);
+        assert_eq!(accent_variants("λυσα"), Vec::<String>::new());
+        assert_eq!(without_augment("ἔλυσα"), Some("λυσα".to_string()));
+        assert_eq!(without_augment("λύω"), None);
+        assert_eq!(without_augment("ἐάω"), None);
+    }
+
+    #[test]
+    fn test_distractors() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let target = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Aorist,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let parameters = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present, HcTense::Future, HcTense::Aorist],
+            voices: vec![HcVoice::Active, HcVoice::Middle],
+            moods: vec![HcMood::Indicative, HcMood::Subjunctive],
+        };
+        let correct = form_string(&target).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let ds = distractors(&target, 4, &parameters, &mut rng);
+        assert_eq!(ds.len(), 4);
+        for (i, d) in ds.iter().enumerate() {
+            assert!(!same_form(&d.form, &correct));
+            assert!(ds[..i].iter().all(|other| !same_form(&other.form, &d.form)));
+            if let DistractorKind::Param(_) = d.kind {
+                let cell = d.cell.as_ref().unwrap();
+                let changes = describe_changes(&target, cell);
+                assert_eq!(changes.len(), 1);
+                assert!(changes[0].starts_with(&d.kind.to_string()));
+                assert_eq!(form_string(cell).as_ref(), Some(&d.form));
+            }
+        }
+
+        // asking for more than there are gives every one
+        let all = distractors(&target, 100, &parameters, &mut rng);
+        assert!(all.len() > 4 && all.len() < 100);
+    }
+
+    #[test]
+    fn test_distractors_with_two_spellings() {
+        let luw = "λύω, λύσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        // λύσει
+        let target = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::Third),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Future,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let parameters = VerbParameters {
+            persons: vec![HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular],
+            tenses: vec![HcTense::Future],
+            voices: vec![HcVoice::Active, HcVoice::Middle],
+            moods: vec![HcMood::Indicative],
+        };
+        let correct = form_string(&target).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        for d in distractors(&target, 100, &parameters, &mut rng) {
+            assert!(!same_form(&d.form, &correct), "{}", d.form);
+        }
+
+        // accent variants of both spellings of λύσει, λύσῃ
+        let middle = HcGreekVerbForm {
+            person: Some(HcPerson::Second),
+            voice: HcVoice::Middle,
+            ..target
+        };
+        let forms: Vec<String> = distractors(&middle, 100, &parameters, &mut rng)
+            .into_iter()
+            .filter(|d| d.kind == DistractorKind::Accent)
+            .map(|d| d.form)
+            .collect();
+        assert!(forms.iter().any(|f| same_form(f, "λυσεί")));
+        assert!(forms.iter().any(|f| same_form(f, "λυσῄ")));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::lookup::NUM_CELLS;
+use hoplite_verbs_rs::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+// bit layout of version 1, from the lowest bit; 0 stands for None
+const PERSON: (u32, u32) = (0, 2);
+const NUMBER: (u32, u32) = (2, 2);
+const TENSE: (u32, u32) = (4, 3);
+const VOICE: (u32, u32) = (7, 2);
+const MOOD: (u32, u32) = (9, 3);
+const GENDER: (u32, u32) = (12, 2);
+const CASE: (u32, u32) = (14, 3);
+const FIELDS: [(u32, u32); 7] = [PERSON, NUMBER, TENSE, VOICE, MOOD, GENDER, CASE];
+const VERSION_SHIFT: u32 = 28;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormIdError {
+    /// Written by a version of this crate that this one doesn't know.
+    UnknownVersion(u32),
+    /// A param is out of range, or a bit outside the layout is set.
+    BadValue(u32),
+}
+
+impl fmt::Display for FormIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormIdError::UnknownVersion(v) => write!(f, "unknown form id version {}", v),
+            FormIdError::BadValue(id) => write!(f, "invalid form id {:#x}", id),
+        }
+    }
+}
+
+impl std::error::Error for FormIdError {}
+
+/// Identifies a cell of a verb, finite or not, for storing in filters and progress data.
+/// Each param is packed into its own bits, so ids never collide and decode back to the
+/// same cell, and the top four bits hold the layout version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "u32", try_from = "u32")]
+pub struct FormId(u32);
+
+fn pack(value: Option<i16>, (shift, _): (u32, u32)) -> u32 {
+    value.map_or(0, |v| (v as u32 + 1) << shift)
+}
+
+fn unpack(id: u32, (shift, bits): (u32, u32)) -> Option<i16> {
+    match (id >> shift) & ((1 << bits) - 1) {
+        0 => None,
+        v => Some(v as i16 - 1),
+    }
+}
+
+impl FormId {
+    pub const VERSION: u32 = 1;
+
+    pub fn of(vf: &HcGreekVerbForm) -> FormId {
+        FormId(
+            Self::VERSION << VERSION_SHIFT
+                | pack(vf.person.map(|p| p.to_i16()), PERSON)
+                | pack(vf.number.map(|n| n.to_i16()), NUMBER)
+                | pack(Some(vf.tense.to_i16()), TENSE)
+                | pack(Some(vf.voice.to_i16()), VOICE)
+                | pack(Some(vf.mood.to_i16()), MOOD)
+                | pack(vf.gender.map(|g| g.to_i16()), GENDER)
+                | pack(vf.case.map(|c| c.to_i16()), CASE),
+        )
+    }
+
+    /// For data stored before form ids: the id of a finite cell's `param_hash`.
+    pub fn from_param_hash(hash: u32) -> Option<FormId> {
+        if hash >= NUM_CELLS {
+            return None;
+        }
+        // the same order as param_hash: voice, mood, tense, number, person
+        let param = |below: u32, count: u32| Some(((hash / below) % count) as i16);
+        Some(FormId(
+            Self::VERSION << VERSION_SHIFT
+                | pack(param(1, 3), PERSON)
+                | pack(param(3, 2), NUMBER)
+                | pack(param(6, 6), TENSE)
+                | pack(param(36, 4), MOOD)
+                | pack(param(144, 3), VOICE),
+        ))
+    }
+
+    pub fn version(&self) -> u32 {
+        self.0 >> VERSION_SHIFT
+    }
+
+    /// Sets the verb form's params to this cell's, leaving its verb as it is.
+    pub fn apply(&self, vf: &mut HcGreekVerbForm) {
+        let id = self.0;
+        vf.person = unpack(id, PERSON).map(HcPerson::from_i16);
+        vf.number = unpack(id, NUMBER).map(HcNumber::from_i16);
+        vf.tense = HcTense::from_i16(unpack(id, TENSE).unwrap());
+        vf.voice = HcVoice::from_i16(unpack(id, VOICE).unwrap());
+        vf.mood = HcMood::from_i16(unpack(id, MOOD).unwrap());
+        vf.gender = unpack(id, GENDER).map(HcGender::from_i16);
+        vf.case = unpack(id, CASE).map(HcCase::from_i16);
+    }
+
+    pub fn to_form(&self, verb: Arc<HcGreekVerb>) -> HcGreekVerbForm {
+        let mut vf = HcGreekVerbForm {
+            verb,
+            person: None,
+            number: None,
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        self.apply(&mut vf);
+        vf
+    }
+}
+
+impl From<FormId> for u32 {
+    fn from(id: FormId) -> u32 {
+        id.0
+    }
+}
+
+impl TryFrom<u32> for FormId {
+    type Error = FormIdError;
+
+    fn try_from(id: u32) -> Result<FormId, FormIdError> {
+        let version = id >> VERSION_SHIFT;
+        if version != FormId::VERSION {
+            return Err(FormIdError::UnknownVersion(version));
+        }
+        // param counts: person, number, tense, voice, mood, gender, case
+        let counts = [3, 2, 6, 3, 6, 3, 5];
+        let mut known = FormId::VERSION << VERSION_SHIFT;
+        for (field, count) in FIELDS.into_iter().zip(counts) {
+            if unpack(id, field).is_some_and(|v| v >= count) {
+                return Err(FormIdError::BadValue(id));
+            }
+            known |= ((1 << field.1) - 1) << field.0;
+        }
+        if id & !known != 0
+            || unpack(id, TENSE).is_none()
+            || unpack(id, VOICE).is_none()
+            || unpack(id, MOOD).is_none()
+        {
+            return Err(FormIdError::BadValue(id));
+        }
+        Ok(FormId(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GetRandom;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_form_id() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let participle = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: None,
+            number: Some(HcNumber::Plural),
+            tense: HcTense::Aorist,
+            voice: HcVoice::Passive,
+            mood: HcMood::Participle,
+            gender: Some(HcGender::Neuter),
+            case: Some(HcCase::Vocative),
+        };
+        let infinitive = HcGreekVerbForm {
+            number: None,
+            mood: HcMood::Infinitive,
+            gender: None,
+            case: None,
+            ..participle.clone()
+        };
+
+        let mut ids = HashSet::new();
+        for vf in [&participle, &infinitive] {
+            let id = FormId::of(vf);
+            assert_eq!(id.version(), FormId::VERSION);
+            assert_eq!(FormId::try_from(u32::from(id)), Ok(id));
+            assert_eq!(&id.to_form(verb.clone()), vf);
+            ids.insert(id);
+        }
+        // every finite cell too, through its old param_hash
+        for hash in 0..NUM_CELLS {
+            let id = FormId::from_param_hash(hash).unwrap();
+            let vf = id.to_form(verb.clone());
+            assert_eq!(vf.param_hash(), hash);
+            ids.insert(id);
+        }
+        assert_eq!(ids.len(), NUM_CELLS as usize + 2);
+        assert_eq!(FormId::from_param_hash(NUM_CELLS), None);
+
+        let id = FormId::of(&participle);
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(serde_json::from_str::<FormId>(&json).unwrap(), id);
+
+        let raw = u32::from(id);
+        assert_eq!(
+            FormId::try_from(raw & 0x0fff_ffff | 2 << VERSION_SHIFT),
+            Err(FormIdError::UnknownVersion(2))
+        );
+        // a sixth case
+        let bad = raw & !(7 << CASE.0) | 6 << CASE.0;
+        assert_eq!(FormId::try_from(bad), Err(FormIdError::BadValue(bad)));
+        assert!(serde_json::from_str::<FormId>(&bad.to_string()).is_err());
+    }
+}
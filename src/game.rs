@@ -0,0 +1,449 @@
+use crate::answer::{check_answer, Mistake};
+use crate::clock::Clock;
+use crate::lookup::form_string;
+use crate::{
+    allowed_forms, changed_params, reachable_forms, ChooserError, FormParam, GetRandom,
+    RandomFormOptions,
+};
+use hoplite_verbs_rs::*;
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub struct GameRules {
+    pub time_limit: Duration,
+    pub lives: u32,
+    pub num_changes: u8,
+    pub highest_unit: Option<i16>,
+    pub parameters: VerbParameters,
+    pub options: RandomFormOptions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Player {
+    pub name: String,
+    pub lives: u32,
+    pub score: u32,
+}
+
+impl Player {
+    pub fn is_out(&self) -> bool {
+        self.lives == 0
+    }
+}
+
+/// One player's turn: change `prev` into `form` before the time runs out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    pub player: usize,
+    pub prev: HcGreekVerbForm,
+    pub form: HcGreekVerbForm,
+    pub answer: String,
+    pub started_at: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnOutcome {
+    Correct,
+    Incorrect(Mistake),
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    Playing,
+    /// The last player with lives left, or None if everyone is out.
+    Over {
+        winner: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// No more turns: the game is over.
+    Over,
+    Chooser(ChooserError),
+}
+
+impl From<ChooserError> for GameError {
+    fn from(e: ChooserError) -> Self {
+        GameError::Chooser(e)
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::Over => write!(f, "the game is over"),
+            GameError::Chooser(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// The rules of the Hoplite Challenge: players take turns changing the last form,
+/// lose a life for a wrong answer or running out of time, and are out with no lives left.
+/// Once every reachable cell of a verb has been asked the game moves on to the next verb.
+pub struct Game<C: Clock> {
+    pub rules: GameRules,
+    clock: C,
+    players: Vec<Player>,
+    verbs: Vec<Arc<HcGreekVerb>>,
+    verb_index: usize,
+    current: HcGreekVerbForm,
+    // the params changed to make current
+    last_change: Vec<FormParam>,
+    used: HashSet<u32>,
+    next_player: usize,
+    turn: Option<Turn>,
+}
+
+// the first cell of the verb the rules allow that has another cell to move to
+fn start_form(verb: &Arc<HcGreekVerb>, rules: &GameRules) -> Option<HcGreekVerbForm> {
+    allowed_forms(verb, rules.highest_unit, &rules.parameters, &rules.options)
+        .into_iter()
+        .find(|vf| {
+            !reachable_forms(
+                vf,
+                rules.num_changes,
+                rules.highest_unit,
+                &rules.parameters,
+                Some(&HashSet::from([vf.param_hash()])),
+                &rules.options,
+            )
+            .is_empty()
+        })
+}
+
+impl<C: Clock> Game<C> {
+    /// None if there are no players, or no verb has a cell to ask under the rules.
+    /// Verbs without one are skipped.
+    pub fn new(
+        rules: GameRules,
+        names: &[&str],
+        verbs: Vec<Arc<HcGreekVerb>>,
+        clock: C,
+    ) -> Option<Game<C>> {
+        if names.is_empty() {
+            return None;
+        }
+        let (verb_index, current) = verbs
+            .iter()
+            .enumerate()
+            .find_map(|(i, verb)| Some((i, start_form(verb, &rules)?)))?;
+        // the start form counts as asked
+        let used = HashSet::from([current.param_hash()]);
+        let players = names
+            .iter()
+            .map(|name| Player {
+                name: name.to_string(),
+                lives: rules.lives,
+                score: 0,
+            })
+            .collect();
+        Some(Game {
+            rules,
+            clock,
+            players,
+            verbs,
+            verb_index,
+            current,
+            last_change: vec![],
+            used,
+            next_player: 0,
+            turn: None,
+        })
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn turn(&self) -> Option<&Turn> {
+        self.turn.as_ref()
+    }
+
+    pub fn verb(&self) -> &Arc<HcGreekVerb> {
+        &self.current.verb
+    }
+
+    pub fn state(&self) -> GameState {
+        let left: Vec<usize> = (0..self.players.len())
+            .filter(|i| !self.players[*i].is_out())
+            .collect();
+        // alone, a player plays until out of lives
+        if left.is_empty() || (self.players.len() > 1 && left.len() == 1) {
+            GameState::Over {
+                winner: left.first().copied(),
+            }
+        } else {
+            GameState::Playing
+        }
+    }
+
+    pub fn time_left(&self) -> Option<Duration> {
+        let turn = self.turn.as_ref()?;
+        Some(self.rules.time_limit.saturating_sub(self.elapsed(turn)))
+    }
+
+    // a clock that goes backwards counts as no time passed
+    fn elapsed(&self, turn: &Turn) -> Duration {
+        self.clock.now().saturating_sub(turn.started_at)
+    }
+
+    // a turn is out of time once the whole time limit has passed
+    fn timed_out(&self, turn: &Turn) -> bool {
+        self.elapsed(turn) >= self.rules.time_limit
+    }
+
+    // the next verb with a cell to ask, coming back round to this one if no other has
+    fn move_to_next_verb(&mut self) -> Result<(), GameError> {
+        for i in 1..=self.verbs.len() {
+            let index = (self.verb_index + i) % self.verbs.len();
+            if let Some(form) = start_form(&self.verbs[index], &self.rules) {
+                self.verb_index = index;
+                self.used = HashSet::from([form.param_hash()]);
+                self.current = form;
+                self.last_change = vec![];
+                return Ok(());
+            }
+        }
+        Err(ChooserError::NoFormsForParameters.into())
+    }
+
+    fn unused_forms(&self) -> Vec<HcGreekVerbForm> {
+        reachable_forms(
+            &self.current,
+            self.rules.num_changes,
+            self.rules.highest_unit,
+            &self.rules.parameters,
+            Some(&self.used),
+            &self.options(),
+        )
+    }
+
+    // the rules' options, with the change that made the current form
+    fn options(&self) -> RandomFormOptions {
+        RandomFormOptions {
+            last_change: self.last_change.clone(),
+            ..self.rules.options.clone()
+        }
+    }
+
+    pub fn start_turn(&mut self) -> Result<&Turn, GameError> {
+        self.start_turn_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Starts the next player's turn, or gives the turn in progress again.
+    /// Moves to the next verb first if every reachable cell of this one has been asked.
+    pub fn start_turn_with_rng<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<&Turn, GameError> {
+        if self.turn.is_none() {
+            if self.state() != GameState::Playing {
+                return Err(GameError::Over);
+            }
+
+            let mut forms = self.unused_forms();
+            if forms.is_empty() {
+                self.move_to_next_verb()?;
+                forms = self.unused_forms();
+            }
+
+            // drawn from the reachable cells themselves, so a used cell never comes back
+            let form = forms
+                .choose(rng)
+                .cloned()
+                .ok_or(ChooserError::FilterExhausted)?;
+            let answer = form_string(&form).ok_or(ChooserError::NoFormsForParameters)?;
+            self.used.insert(form.param_hash());
+
+            while self.players[self.next_player].is_out() {
+                self.next_player = (self.next_player + 1) % self.players.len();
+            }
+            self.turn = Some(Turn {
+                player: self.next_player,
+                prev: self.current.clone(),
+                form,
+                answer,
+                started_at: self.clock.now(),
+            });
+        }
+        Ok(self.turn.as_ref().unwrap())
+    }
+
+    /// Grades the answer for the turn in progress; an answer once the time limit has
+    /// passed is timed out. None if no turn is in progress.
+    pub fn answer(&mut self, answer: &str) -> Option<TurnOutcome> {
+        let turn = self.turn.as_ref()?;
+        let outcome = if self.timed_out(turn) {
+            TurnOutcome::TimedOut
+        } else {
+            match check_answer(answer, &turn.answer) {
+                Ok(()) => TurnOutcome::Correct,
+                Err(mistake) => TurnOutcome::Incorrect(mistake),
+            }
+        };
+        self.end_turn(outcome);
+        Some(outcome)
+    }
+
+    /// For front ends to poll: ends the turn in progress if its time has run out.
+    pub fn check_timeout(&mut self) -> Option<TurnOutcome> {
+        if !self.timed_out(self.turn.as_ref()?) {
+            return None;
+        }
+        self.end_turn(TurnOutcome::TimedOut);
+        Some(TurnOutcome::TimedOut)
+    }
+
+    fn end_turn(&mut self, outcome: TurnOutcome) {
+        let Some(turn) = self.turn.take() else {
+            return;
+        };
+        let player = &mut self.players[turn.player];
+        if outcome == TurnOutcome::Correct {
+            player.score += 1;
+        } else {
+            player.lives = player.lives.saturating_sub(1);
+        }
+        self.last_change = changed_params(&turn.prev, &turn.form);
+        self.current = turn.form;
+        self.next_player = (turn.player + 1) % self.players.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_game() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let paideuw = "παιδεύω, παιδεύσω, ἐπαίδευσα, πεπαίδευκα, πεπαίδευμαι, ἐπαιδεύθην";
+        let verbs = vec![
+            Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap()),
+            Arc::new(HcGreekVerb::from_string(2, paideuw, REGULAR, 0).unwrap()),
+        ];
+        let rules = GameRules {
+            time_limit: Duration::from_secs(30),
+            lives: 2,
+            num_changes: 1,
+            highest_unit: None,
+            parameters: VerbParameters {
+                persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+                numbers: vec![HcNumber::Singular],
+                tenses: vec![HcTense::Present],
+                voices: vec![HcVoice::Active],
+                moods: vec![HcMood::Indicative],
+            },
+            options: RandomFormOptions::default(),
+        };
+        let time = Rc::new(Cell::new(Duration::ZERO));
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut game = Game::new(rules, &["a", "b"], verbs, TestClock(time.clone())).unwrap();
+
+        let turn = game.start_turn_with_rng(&mut rng).unwrap().clone();
+        assert_eq!(turn.player, 0);
+        time.set(Duration::from_secs(10));
+        assert_eq!(game.time_left(), Some(Duration::from_secs(20)));
+        assert_eq!(game.answer(&turn.answer), Some(TurnOutcome::Correct));
+        assert_eq!(game.players()[0].score, 1);
+
+        // the second person singular is the only cell left, then the verb changes
+        let turn = game.start_turn_with_rng(&mut rng).unwrap().clone();
+        assert_eq!(turn.player, 1);
+        assert_eq!(turn.prev.verb.id, 1);
+        assert!(matches!(
+            game.answer("οὐδέν"),
+            Some(TurnOutcome::Incorrect(_))
+        ));
+        assert_eq!(game.players()[1].lives, 1);
+
+        let turn = game.start_turn_with_rng(&mut rng).unwrap().clone();
+        assert_eq!(turn.player, 0);
+        assert_eq!(game.verb().id, 2);
+        assert_eq!(game.check_timeout(), None);
+        time.set(Duration::from_secs(5));
+        assert_eq!(game.time_left(), Some(Duration::from_secs(30)));
+        assert_eq!(game.check_timeout(), None);
+        time.set(Duration::from_secs(50));
+        assert_eq!(game.check_timeout(), Some(TurnOutcome::TimedOut));
+        assert_eq!(game.players()[0].lives, 1);
+
+        game.start_turn_with_rng(&mut rng).unwrap();
+        // exactly at the limit is already too late
+        time.set(Duration::from_secs(80));
+        assert_eq!(game.time_left(), Some(Duration::ZERO));
+        assert_eq!(game.answer(""), Some(TurnOutcome::TimedOut));
+        assert_eq!(game.state(), GameState::Over { winner: Some(0) });
+        assert_eq!(game.start_turn_with_rng(&mut rng), Err(GameError::Over));
+    }
+
+    #[test]
+    fn test_game_start_forms_and_used_cells() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let paideuw = "παιδεύω, παιδεύσω, ἐπαίδευσα, πεπαίδευκα, πεπαίδευμαι, ἐπαιδεύθην";
+        let verbs = vec![
+            Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 20).unwrap()),
+            Arc::new(HcGreekVerb::from_string(2, paideuw, REGULAR, 2).unwrap()),
+        ];
+        let rules = GameRules {
+            time_limit: Duration::from_secs(30),
+            lives: 1,
+            num_changes: 1,
+            highest_unit: Some(5),
+            parameters: VerbParameters {
+                persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+                numbers: vec![HcNumber::Singular, HcNumber::Plural],
+                tenses: vec![HcTense::Present, HcTense::Future],
+                voices: vec![HcVoice::Active],
+                moods: vec![HcMood::Subjunctive, HcMood::Indicative],
+            },
+            options: RandomFormOptions::default(),
+        };
+        let time = Rc::new(Cell::new(Duration::ZERO));
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut game = Game::new(rules, &["a"], verbs, TestClock(time)).unwrap();
+
+        // the verb past the unit is skipped, and the start form is the first allowed cell
+        assert_eq!(game.verb().id, 2);
+        let turn = game.start_turn_with_rng(&mut rng).unwrap().clone();
+        assert_eq!(turn.prev.mood, HcMood::Subjunctive);
+        assert_eq!(turn.prev.tense, HcTense::Present);
+
+        // every cell of the verb is asked at most once before the game comes back round to it
+        let mut asked = vec![turn.prev.param_hash()];
+        let mut turn = Some(turn);
+        while let Some(t) = turn {
+            assert!(!asked.contains(&t.form.param_hash()), "{:?}", t.form);
+            asked.push(t.form.param_hash());
+            assert_eq!(game.answer(&t.answer), Some(TurnOutcome::Correct));
+            let next = game.start_turn_with_rng(&mut rng).unwrap();
+            turn = (next.prev == t.form).then(|| next.clone());
+        }
+        assert!(asked.len() > 2);
+        assert_eq!(game.turn().unwrap().prev.param_hash(), asked[0]);
+
+        // no verb in the unit: no game
+        let rules = GameRules {
+            highest_unit: Some(1),
+            ..game.rules
+        };
+        let verbs = vec![Arc::new(
+            HcGreekVerb::from_string(1, luw, REGULAR, 20).unwrap(),
+        )];
+        let time = Rc::new(Cell::new(Duration::ZERO));
+        assert!(Game::new(rules, &["a"], verbs, TestClock(time)).is_none());
+    }
+}
@@ -1,14 +1,247 @@
 pub use hoplite_verbs_rs::*;
 use rand::prelude::SliceRandom;
+use rand::Rng;
 use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+pub mod adaptive;
+pub mod answer;
+pub mod clock;
+pub mod curriculum;
+pub mod derivation;
+pub mod distractors;
+pub mod form_id;
+pub mod game;
+pub mod lookup;
+pub mod names;
+pub mod parsing;
+pub mod scheduler;
+pub mod session;
+pub mod verbs;
+pub mod weights;
+
+use curriculum::{BlockReason, Curriculum};
+use form_id::{FormId, FormIdError};
+use weights::{choose_value, value_weight, ParamWeights};
+
+/// Why `random_form_with_options` could not find an acceptable form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChooserError {
+    /// Real forms were available for the unit, but every one was rejected by the filter
+    /// (or by the middle/passive rule).
+    FilterExhausted,
+    /// The verb has real forms for these parameters, but the unit blocks all of them.
+    UnitBlocksEverything(i16),
+    /// `get_form` gave an error or "—" for every candidate.
+    NoFormsForParameters,
+    /// With `exact_changes`, fewer params than asked for can be changed.
+    TooFewChanges { requested: u8, possible: u8 },
+}
+
+impl fmt::Display for ChooserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChooserError::FilterExhausted => {
+                write!(f, "every acceptable form has already been used")
+            }
+            ChooserError::UnitBlocksEverything(unit) => {
+                write!(f, "unit {} blocks every form for these parameters", unit)
+            }
+            ChooserError::NoFormsForParameters => {
+                write!(f, "the verb has no forms for these parameters")
+            }
+            ChooserError::TooFewChanges {
+                requested,
+                possible,
+            } => write!(
+                f,
+                "{} changes were asked for, but only {} can be made",
+                requested, possible
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChooserError {}
+
+/// How many candidates `random_form_with_options` tries before giving up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryBudget {
+    /// Stop honouring the filter after this many tries; `None` never ignores it.
+    pub ignore_filter_after: Option<u32>,
+    pub max_tries: u32,
+}
+
+impl RetryBudget {
+    /// Never ignores the filter, for filters that must hold, like `lookup::ambiguous_hashes`.
+    pub fn strict() -> Self {
+        RetryBudget {
+            ignore_filter_after: None,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for RetryBudget {
+    fn default() -> Self {
+        RetryBudget {
+            ignore_filter_after: Some(2000),
+            max_tries: 4000,
+        }
+    }
+}
+
+/// Why `random_form_traced` turned a candidate down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    /// `get_form` gave an error
+    Illegal,
+    /// `get_form` gave "—"
+    Dash,
+    /// middle to passive (or back) outside the aorist and future
+    MiddlePassive,
+    BlockedForUnit(BlockReason),
+    /// already in the filter
+    Filtered,
+    /// with `exact_changes`, fewer params were changed than asked for
+    TooFewChanges,
+}
+
+/// A candidate `random_form_traced` turned down.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RejectedCandidate {
+    pub form: HcGreekVerbForm,
+    /// The last step of `get_form`, or its error.
+    pub result: Result<String, String>,
+    pub reasons: Vec<RejectionReason>,
+}
+
+/// The genders and cases participles are asked in, when `parameters.moods` has the participle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParticipleParameters {
+    pub genders: Vec<HcGender>,
+    pub cases: Vec<HcCase>,
+}
+
+impl Default for ParticipleParameters {
+    // the vocative is left out: it is nearly always the same as the nominative
+    fn default() -> Self {
+        ParticipleParameters {
+            genders: vec![HcGender::Masculine, HcGender::Feminine, HcGender::Neuter],
+            cases: vec![
+                HcCase::Nominative,
+                HcCase::Genitive,
+                HcCase::Dative,
+                HcCase::Accusative,
+            ],
+        }
+    }
+}
+
+/// Settings for `random_form_with_options` and `reachable_forms`.
+#[derive(Debug, Clone, Default)]
+pub struct RandomFormOptions {
+    pub budget: RetryBudget,
+    /// Unit gating rules; `None` uses the built-in Hoplite sequence.
+    pub curriculum: Option<Arc<Curriculum>>,
+    /// Which params `change_params` prefers to change.
+    pub weights: ParamWeights,
+    pub participles: ParticipleParameters,
+    /// Params that are never changed.
+    pub frozen: Vec<FormParam>,
+    /// The params changed to make the form we start from; `DrillSession` and `Game` fill it in.
+    pub last_change: Vec<FormParam>,
+    /// Don't change the params in `last_change` again, unless there are too few others.
+    pub avoid_last_change: bool,
+    /// Fail with `ChooserError::TooFewChanges` rather than change fewer params than asked for.
+    /// `random_form` doesn't take options, so it never fails this way.
+    pub exact_changes: bool,
+}
 
+/// A param of a form that `change_params` can change: one of `HcParameters`, or a
+/// participle's declension, its gender and case, which change together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormParam {
+    Param(HcParameters),
+    Declension,
+}
+
+impl FormParam {
+    pub const ALL: [FormParam; 6] = [
+        FormParam::Param(HcParameters::Person),
+        FormParam::Param(HcParameters::Number),
+        FormParam::Param(HcParameters::Tense),
+        FormParam::Param(HcParameters::Mood),
+        FormParam::Param(HcParameters::Voice),
+        FormParam::Declension,
+    ];
+}
+
+impl From<HcParameters> for FormParam {
+    fn from(param: HcParameters) -> FormParam {
+        FormParam::Param(param)
+    }
+}
+
+/// A value of one of the params of a form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamValue {
+    Person(HcPerson),
+    Number(HcNumber),
+    Tense(HcTense),
+    Mood(HcMood),
+    Voice(HcVoice),
+    Declension(HcGender, HcCase),
+}
+
+/// A param that differs between two forms, with its value in each; `None` where the form's
+/// mood doesn't have the param, e.g. the person of an infinitive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamChange {
+    pub param: FormParam,
+    pub old: Option<ParamValue>,
+    pub new: Option<ParamValue>,
+}
+
+impl RandomFormOptions {
+    pub fn curriculum(&self) -> &Curriculum {
+        self.curriculum
+            .as_deref()
+            .unwrap_or_else(|| Curriculum::hoplite())
+    }
+}
+
+/// A chosen form, with how it was found and the params that differ from the form it came from.
+pub type RandomForm = (HcGreekVerbForm, Diagnostics, Vec<ParamChange>);
+
+/// `change_params_with_options` and `random_form_with_options` need a sized `Self`; the rest
+/// can be called on `dyn GetRandom`.
 pub trait GetRandom {
+    /// Returns the params it changed; a participle's declension counts as its person.
     fn change_params(
         &mut self,
         n_params_to_change: u8,
         parameters: &VerbParameters,
         params_do_not_change: &mut [HcParameters],
     ) -> Vec<HcParameters>;
+    /// `change_params` with the caller's rng, so a seeded rng gives a repeatable sequence, and
+    /// with the weights, frozen params and participle genders and cases of `options`.
+    fn change_params_with_options<R: Rng + ?Sized>(
+        &mut self,
+        n_params_to_change: u8,
+        parameters: &VerbParameters,
+        options: &RandomFormOptions,
+        rng: &mut R,
+    ) -> Vec<FormParam>
+    where
+        Self: Sized;
+    /// `random_form_with_options` with the default options.
+    ///
+    /// # Panics
+    ///
+    /// If the verb has no acceptable form for the parameters and unit; use
+    /// `random_form_with_options` to get the `ChooserError` instead.
     fn random_form(
         &self,
         num_changes: u8,
@@ -16,9 +249,25 @@ pub trait GetRandom {
         parameters: &VerbParameters,
         filter_forms: Option<&HashSet<u32>>,
     ) -> (HcGreekVerbForm, Diagnostics);
+    /// A form `num_changes` changes from `self`, with the params that differ and their old and
+    /// new values. It may have fewer changes when too few params can change, unless
+    /// `options.exact_changes` is set.
+    fn random_form_with_options<R: Rng + ?Sized>(
+        &self,
+        num_changes: u8,
+        highest_unit: Option<i16>,
+        parameters: &VerbParameters,
+        filter_forms: Option<&HashSet<u32>>,
+        options: &RandomFormOptions,
+        rng: &mut R,
+    ) -> Result<RandomForm, ChooserError>
+    where
+        Self: Sized;
     fn block_for_hq_unit(&self, unit: Option<i16>) -> bool;
     fn block_middle_passive(&self, new_form: &HcGreekVerbForm) -> bool;
     fn param_hash(&self) -> u32;
+    /// Panics if `value` is neither a `param_hash` nor a `FormId`; `try_extract_params_from_hash`
+    /// returns the error instead.
     fn extract_params_from_hash(&mut self, value: u32);
 }
 
@@ -31,215 +280,218 @@ impl GetRandom for HcGreekVerbForm {
         parameters: &VerbParameters,
         filter_forms: Option<&HashSet<u32>>, //previously used forms we don't want to return
     ) -> (HcGreekVerbForm, Diagnostics) {
-        let mut pf: HcGreekVerbForm;
-        let mut num_skipped = 0;
-        let mut ignore_filter = false;
-
-        let mut diag = Diagnostics {
-            dash: 0,
-            middle_passive: 0,
-            blocked_for_unit: 0,
-            filtered: 0,
-            illegal: 0,
-        };
-
-        loop {
-            pf = self.clone();
-            pf.change_params(
+        let (pf, diag, _) = self
+            .random_form_with_options(
                 num_changes,
+                highest_unit,
                 parameters,
-                &mut [], //HcParameters::Person, HcParameters::Number
-            );
-            let vf = pf.get_form(false);
-            if num_skipped > 2000 {
-                // println!(
-                //     "AAABBB error: {}",
-                //     if filter_forms.is_some() {
-                //         filter_forms.unwrap().len()
-                //     } else {
-                //         222
-                //     }
-                // );
-                //error!("random form 2000 cycles");
-                ignore_filter = true;
-            } else if num_skipped > 4000 {
-                // println!(
-                //     "AAABBB2 error: {}",
-                //     if filter_forms.is_some() {
-                //         filter_forms.unwrap().len()
-                //     } else {
-                //         222
-                //     }
-                // );
-                //error!("random form 4000 cycles");
-                break;
-            }
-            num_skipped += 1;
-            match vf {
-                Ok(res) => {
-                    if res.last().unwrap().form == "—"
-                        || self.block_middle_passive(&pf)
-                        || pf.block_for_hq_unit(highest_unit)
-                        || (filter_forms.is_some()
-                            && !ignore_filter
-                            && filter_forms.unwrap().contains(&pf.param_hash()))
-                    {
-                        if self.block_middle_passive(&pf) {
-                            diag.middle_passive += 1;
-                        } else if pf.block_for_hq_unit(highest_unit) {
-                            diag.blocked_for_unit += 1;
-                        } else if filter_forms.is_some()
-                            && !ignore_filter
-                            && filter_forms.unwrap().contains(&pf.param_hash())
-                        {
-                            diag.filtered += 1;
-                        } else if res.last().unwrap().form == "—" {
-                            diag.dash += 1;
-                        }
-
-                        let _reason = if self.block_middle_passive(&pf) {
-                            String::from("middle/passive just used")
-                        } else if pf.block_for_hq_unit(highest_unit) {
-                            format!("not in unit: {:?}", highest_unit)
-                        } else if filter_forms.is_some()
-                            && !ignore_filter
-                            && filter_forms.unwrap().contains(&pf.param_hash())
-                        {
-                            "already used".to_string()
-                        } else if res.last().unwrap().form == "—" {
-                            format!("block bad form {:?}", res)
-                        } else {
-                            String::from("unknown reason")
-                        };
-                        // println!(
-                        //     "\t{}: {:?} {:?}",
-                        //     num_skipped,
-                        //     res.last().unwrap().form,
-                        //     reason
-                        // );
-
-                        continue;
-                    } else {
-                        //println!("{}", res.last().unwrap().form);
-                        break;
-                    }
-                } //only 3rd pl consonant stem perfects/pluperfects return - now
-                Err(_e) => {
-                    diag.illegal += 1;
-                    //println!("\t{}: {:?}", num_skipped, e);
-                    continue;
-                }
-            }
-        }
+                filter_forms,
+                &RandomFormOptions::default(),
+                &mut rand::thread_rng(),
+            )
+            .unwrap_or_else(|e| panic!("no random form: {}", e));
         (pf, diag)
     }
 
+    fn random_form_with_options<R: Rng + ?Sized>(
+        &self,
+        num_changes: u8,
+        highest_unit: Option<i16>,
+        parameters: &VerbParameters,
+        filter_forms: Option<&HashSet<u32>>,
+        options: &RandomFormOptions,
+        rng: &mut R,
+    ) -> Result<RandomForm, ChooserError> {
+        let (res, diag) = choose_random_form(
+            self,
+            num_changes,
+            highest_unit,
+            parameters,
+            filter_forms,
+            options,
+            rng,
+            None,
+        );
+        res.map(|pf| {
+            let changes = param_changes(self, &pf);
+            (pf, diag, changes)
+        })
+    }
+
     // num params to change must be equal or less than num params with more than one value
-    // params_do_not_change: pass in params from last change, so we don't change the same ones again
+    // params_do_not_change: params that are never changed, e.g. to drill one person and number
     fn change_params(
         &mut self,
         n_params_to_change: u8,
         parameters: &VerbParameters,
         params_do_not_change: &mut [HcParameters],
     ) -> Vec<HcParameters> {
-        let mut possible_params = vec![
-            HcParameters::Person,
-            HcParameters::Number,
-            HcParameters::Tense,
-            HcParameters::Mood,
-            HcParameters::Voice,
-        ];
+        let options = RandomFormOptions {
+            frozen: params_do_not_change.iter().map(|p| (*p).into()).collect(),
+            ..Default::default()
+        };
+        self.change_params_with_options(
+            n_params_to_change,
+            parameters,
+            &options,
+            &mut rand::thread_rng(),
+        )
+        .into_iter()
+        .map(|p| match p {
+            FormParam::Param(p) => p,
+            FormParam::Declension => HcParameters::Person,
+        })
+        .collect()
+    }
 
-        if parameters.persons.len() == 1 {
-            self.person = Some(parameters.persons[0]);
-            possible_params.retain(|e| *e != HcParameters::Person);
-        }
-        if parameters.numbers.len() == 1 {
-            self.number = Some(parameters.numbers[0]);
-            possible_params.retain(|e| *e != HcParameters::Number);
-        }
-        if parameters.tenses.len() == 1 {
-            self.tense = parameters.tenses[0];
-            possible_params.retain(|e| *e != HcParameters::Tense);
-        }
-        if parameters.moods.len() == 1 {
-            self.mood = parameters.moods[0];
-            possible_params.retain(|e| *e != HcParameters::Mood);
-        }
-        if parameters.voices.len() == 1 {
-            self.voice = parameters.voices[0];
-            possible_params.retain(|e| *e != HcParameters::Voice);
-        }
+    fn change_params_with_options<R: Rng + ?Sized>(
+        &mut self,
+        n_params_to_change: u8,
+        parameters: &VerbParameters,
+        options: &RandomFormOptions,
+        rng: &mut R,
+    ) -> Vec<FormParam> {
+        let weights = &options.weights;
+        let mut possible_params = fix_single_value_params(self, parameters, &options.participles);
 
-        if self.person.is_none() || self.number.is_none() || possible_params.is_empty() {
+        if !fits_mood(self) || possible_params.is_empty() {
             return vec![];
         }
 
-        let mut rng = rand::thread_rng();
+        possible_params.retain(|p| !options.frozen.contains(p));
 
-        if !params_do_not_change.is_empty() && possible_params.len() > 1 {
-            params_do_not_change.shuffle(&mut rng); //shuffle, so not always first param
-            if let Some(aa) = params_do_not_change.first() {
-                possible_params.retain(|e| *e != *aa);
-            }
+        if weights.has_value_weights() {
+            possible_params.retain(|p| weights.can_change(self, p, parameters));
         }
 
-        possible_params.shuffle(&mut rng);
-        possible_params.truncate(n_params_to_change.into());
+        let n = n_params_to_change.into();
+        let mut possible_params = if options.avoid_last_change {
+            // the params of the last change only make up for too few others
+            let (repeats, others): (Vec<_>, Vec<_>) = possible_params
+                .into_iter()
+                .partition(|p| options.last_change.contains(p));
+            let mut chosen = weights.choose_params(others, n, rng);
+            if chosen.len() < n {
+                let more = weights.choose_params(repeats, n - chosen.len(), rng);
+                chosen.extend(more);
+            }
+            chosen
+        } else {
+            weights.choose_params(possible_params, n, rng)
+        };
 
+        // the mood first when it can cross between finite and non-finite moods, so the other
+        // params change within the new mood; otherwise in the drawn order, as before weights
+        let mood_param = FormParam::from(HcParameters::Mood);
+        if !is_finite(self.mood) || parameters.moods.iter().any(|m| !is_finite(*m)) {
+            possible_params.sort_by_key(|p| *p != mood_param);
+        }
+        let mut changed = vec![];
         for p in &possible_params {
             match p {
-                HcParameters::Person => {
-                    self.person = Some(
-                        **parameters
+                FormParam::Declension => {
+                    // e.g. after a change away from the participle
+                    if self.mood != HcMood::Participle {
+                        continue;
+                    }
+                    let Some(form) = declension_alternatives(self, &options.participles)
+                        .choose(rng)
+                        .cloned()
+                    else {
+                        continue;
+                    };
+                    *self = form;
+                }
+                FormParam::Param(HcParameters::Person) => {
+                    if !is_finite(self.mood) {
+                        continue;
+                    }
+                    let Some(person) = choose_value(
+                        &parameters
                             .persons
                             .iter()
-                            .filter(|x| **x != self.person.unwrap())
-                            .collect::<Vec<_>>()
-                            .choose(&mut rand::thread_rng())
-                            .unwrap(),
-                    );
+                            .filter(|x| Some(**x) != self.person)
+                            .collect::<Vec<_>>(),
+                        &weights.persons,
+                        rng,
+                    ) else {
+                        continue;
+                    };
+                    self.person = Some(*person);
                 }
-                HcParameters::Number => {
-                    self.number = Some(
-                        **parameters
+                FormParam::Param(HcParameters::Number) => {
+                    if self.mood == HcMood::Infinitive {
+                        continue;
+                    }
+                    let Some(number) = choose_value(
+                        &parameters
                             .numbers
                             .iter()
-                            .filter(|x| **x != self.number.unwrap())
-                            .collect::<Vec<_>>()
-                            .choose(&mut rand::thread_rng())
-                            .unwrap(),
-                    );
+                            .filter(|x| Some(**x) != self.number)
+                            .collect::<Vec<_>>(),
+                        &weights.numbers,
+                        rng,
+                    ) else {
+                        continue;
+                    };
+                    self.number = Some(*number);
                 }
-                HcParameters::Tense => {
-                    self.tense = **parameters
-                        .tenses
-                        .iter()
-                        .filter(|x| **x != self.tense)
-                        .collect::<Vec<_>>()
-                        .choose(&mut rand::thread_rng())
-                        .unwrap();
+                FormParam::Param(HcParameters::Tense) => {
+                    let Some(tense) = choose_value(
+                        &parameters
+                            .tenses
+                            .iter()
+                            .filter(|x| **x != self.tense)
+                            .collect::<Vec<_>>(),
+                        &weights.tenses,
+                        rng,
+                    ) else {
+                        continue;
+                    };
+                    self.tense = *tense;
                 }
-                HcParameters::Voice => {
-                    self.voice = **parameters
-                        .voices
-                        .iter()
-                        .filter(|x| **x != self.voice)
-                        .collect::<Vec<_>>()
-                        .choose(&mut rand::thread_rng())
-                        .unwrap();
+                FormParam::Param(HcParameters::Voice) => {
+                    let Some(voice) = choose_value(
+                        &parameters
+                            .voices
+                            .iter()
+                            .filter(|x| **x != self.voice)
+                            .collect::<Vec<_>>(),
+                        &weights.voices,
+                        rng,
+                    ) else {
+                        continue;
+                    };
+                    self.voice = *voice;
                 }
-                HcParameters::Mood => {
-                    self.mood = **parameters
-                        .moods
-                        .iter()
-                        .filter(|x| **x != self.mood)
-                        .collect::<Vec<_>>()
-                        .choose(&mut rand::thread_rng())
-                        .unwrap();
+                FormParam::Param(HcParameters::Mood) => {
+                    let Some(mood) = choose_value(
+                        &parameters
+                            .moods
+                            .iter()
+                            .filter(|x| **x != self.mood)
+                            .collect::<Vec<_>>(),
+                        &weights.moods,
+                        rng,
+                    ) else {
+                        continue;
+                    };
+                    let mut forms = forms_in_mood(self, *mood, parameters, &options.participles);
+                    // only draw when there is a choice, so finite moods use the rng as before
+                    let i = if forms.len() > 1 {
+                        rng.gen_range(0..forms.len())
+                    } else {
+                        0
+                    };
+                    if i >= forms.len() {
+                        continue;
+                    }
+                    *self = forms.swap_remove(i);
                 }
             }
+            changed.push(*p);
         }
+        possible_params.retain(|p| changed.contains(p));
         possible_params
     }
 
@@ -256,115 +508,28 @@ impl GetRandom for HcGreekVerbForm {
     }
 
     fn block_for_hq_unit(&self, unit: Option<i16>) -> bool {
-        match unit {
-            Some(unit) => {
-                let is_mi_verb = self.verb.pps[0].ends_with("μι");
-                let is_isthmi = self.verb.pps[0].ends_with("στημι");
-
-                let is_isthmi_perf = is_isthmi
-                    && (self.tense == HcTense::Aorist
-                        || self.tense == HcTense::Perfect
-                        || self.tense == HcTense::Pluperfect);
-
-                let is_future_optative =
-                    self.tense == HcTense::Future && self.mood == HcMood::Optative;
-
-                let is_consonant_stem_third_plural = self.is_consonant_stem("")
-                    && (self.tense == HcTense::Perfect || self.tense == HcTense::Pluperfect)
-                    && (self.voice == HcVoice::Middle || self.voice == HcVoice::Passive)
-                    && self.person == Some(HcPerson::Third)
-                    && self.number == Some(HcNumber::Plural);
-
-                if unit <= 2 {
-                    //2 and under active indicative and not perfect or pluperfect
-                    if self.tense == HcTense::Perfect
-                        || self.tense == HcTense::Pluperfect
-                        || self.voice != HcVoice::Active
-                        || self.mood != HcMood::Indicative
-                        || is_mi_verb
-                    {
-                        return true;
-                    }
-                } else if unit <= 4 {
-                    //4 and under must be active, no imperatives, no future optative
-                    if self.voice != HcVoice::Active
-                        || self.mood == HcMood::Imperative
-                        || is_mi_verb
-                        || is_future_optative
-                    {
-                        return true;
-                    }
-                } else if unit <= 6 {
-                    //6 and under can't be middle, no imperatives, no future optative
-                    if self.voice == HcVoice::Middle
-                        || self.mood == HcMood::Imperative
-                        || is_mi_verb
-                        || is_future_optative
-                        || is_consonant_stem_third_plural
-                    {
-                        return true;
-                    }
-                } else if unit <= 10 {
-                    //10 and under no imperatives, no future optative
-                    if self.mood == HcMood::Imperative
-                        || is_mi_verb
-                        || is_future_optative
-                        || is_consonant_stem_third_plural
-                    {
-                        return true;
-                    }
-                } else if unit <= 11 {
-                    //11 and under no aorists of mi verbs, no perf/plup of isthmi, no future optative
-                    if is_mi_verb || is_future_optative || is_consonant_stem_third_plural {
-                        return true;
-                    }
-                } else if unit <= 12 {
-                    //12 and under no aorists of mi verbs, no perf/plup of isthmi, no future optative
-                    if (is_mi_verb && self.tense == HcTense::Aorist)
-                        || is_isthmi_perf
-                        || is_future_optative
-                        || is_consonant_stem_third_plural
-                    {
-                        return true;
-                    }
-                    // todo deiknumi verbs?
-                } else if unit <= 15 {
-                    //15 and under no future optative
-                    if is_future_optative || is_consonant_stem_third_plural {
-                        return true;
-                    }
-                } else if unit <= 19 {
-                    //19 and under no 3rd plural of consonant stem perf/plup mid/pass
-                    if is_consonant_stem_third_plural {
-                        return true;
-                    }
-                }
-                false
-            }
-            None => false,
-        }
+        Curriculum::hoplite().blocks(self, unit)
     }
 
-    //only call on finite verbs, maybe change to return Option<u32> to handle non-finites?
+    // finite cells keep their old values below lookup::NUM_CELLS, so stored filters still work;
+    // any other cell (infinitives, participles) gives its FormId, which is far above them
     fn param_hash(&self) -> u32 {
         let p_count = 3;
         let n_count = 2;
         let t_count = 6;
         let m_count = 4;
 
-        let voice = self.voice.to_i16();
+        let (Some(person), Some(number)) = (self.person, self.number) else {
+            return FormId::of(self).into();
+        };
         let mood = self.mood.to_i16();
+        if mood >= m_count {
+            return FormId::of(self).into();
+        }
+        let voice = self.voice.to_i16();
         let tense = self.tense.to_i16();
-        let number = if self.number.is_some() {
-            self.number.unwrap().to_i16()
-        } else {
-            2 //panic!() //add an extra number, in case of None: it just has to be unique
-        };
-        let person = if self.person.is_some() {
-            self.person.unwrap().to_i16()
-        } else {
-            3 //panic!() //add an extra number, in case of None: it just has to be unique
-        };
+        let number = number.to_i16();
+        let person = person.to_i16();
 
         //calculate unique hash from param values
         (voice * m_count * t_count * n_count * p_count
@@ -377,96 +542,687 @@ impl GetRandom for HcGreekVerbForm {
     }
 
     fn extract_params_from_hash(&mut self, value: u32) {
-        let p_count = 3;
-        let n_count = 2;
-        let t_count = 6;
-        let m_count = 4;
+        try_extract_params_from_hash(self, value).unwrap_or_else(|e| panic!("{}", e));
+    }
+}
 
-        let voice = value / (m_count * t_count * n_count * p_count);
-        let remaining = value % (m_count * t_count * n_count * p_count);
+// a legacy param_hash below NUM_CELLS, or a FormId; anything else leaves vf as it is
+pub fn try_extract_params_from_hash(
+    vf: &mut HcGreekVerbForm,
+    value: u32,
+) -> Result<(), FormIdError> {
+    let id = match FormId::from_param_hash(value) {
+        Some(id) => id,
+        None => FormId::try_from(value)?,
+    };
+    id.apply(vf);
+    Ok(())
+}
 
-        let mood = remaining / (t_count * n_count * p_count);
-        let remaining = remaining % (t_count * n_count * p_count);
+// why block_for_hq_unit blocks this form, e.g. to answer "why did I never get the future optative?"
+pub fn block_reasons_for_hq_unit(vf: &HcGreekVerbForm, unit: Option<i16>) -> Vec<BlockReason> {
+    Curriculum::hoplite().block_reasons(vf, unit)
+}
 
-        let tense = remaining / (n_count * p_count);
-        let remaining = remaining % (n_count * p_count);
+// every form random_form could return, in a fixed order: choosing from it uniformly always
+// finishes after the same amount of work, and an empty Vec means there is nothing to choose
+pub fn reachable_forms(
+    vf: &HcGreekVerbForm,
+    num_changes: u8,
+    highest_unit: Option<i16>,
+    parameters: &VerbParameters,
+    filter_forms: Option<&HashSet<u32>>,
+    options: &RandomFormOptions,
+) -> Vec<HcGreekVerbForm> {
+    let parameters = &verbs::available_parameters(&vf.verb, parameters);
+    if parameters.tenses.is_empty() || parameters.voices.is_empty() {
+        return vec![];
+    }
+    let mut start = vf.clone();
+    let Some(mut possible_params) = changeable_params(&mut start, parameters, options) else {
+        return vec![];
+    };
+    if possible_params.is_empty()
+        || (options.exact_changes && possible_params.len() < num_changes.into())
+    {
+        return vec![];
+    }
 
-        let number = remaining / p_count;
-        let person = remaining % p_count;
+    // change_params changes as many params as it can, up to num_changes, the mood first
+    let n = possible_params.len().min(num_changes.into());
+    possible_params.sort_by_key(|p| *p != FormParam::from(HcParameters::Mood));
+    // and only changes the params of the last change when the others run out
+    let avoided = |p: &FormParam| options.avoid_last_change && options.last_change.contains(p);
+    let others = possible_params.iter().filter(|p| !avoided(p)).count();
+
+    let mut forms = vec![];
+    let mut seen = HashSet::new();
+    for mask in 0u32..(1 << possible_params.len()) {
+        let in_mask = |i: usize| mask & (1 << i) != 0;
+        if mask.count_ones() as usize != n
+            || (0..possible_params.len())
+                .filter(|i| in_mask(*i) && !avoided(&possible_params[*i]))
+                .count()
+                != others.min(n)
+        {
+            continue;
+        }
+        let mut candidates = vec![start.clone()];
+        for (i, p) in possible_params.iter().enumerate() {
+            if mask & (1 << i) != 0 {
+                candidates = candidates
+                    .iter()
+                    .flat_map(|c| match alternative_values(c, p, parameters, options) {
+                        // e.g. the person, after a change to the infinitive
+                        alts if alts.is_empty() && !has_param(c, p) && !options.exact_changes => {
+                            vec![c.clone()]
+                        }
+                        alts => alts,
+                    })
+                    .collect();
+            }
+        }
+        forms.extend(candidates.into_iter().filter(|pf| {
+            seen.insert(pf.param_hash())
+                && is_acceptable_form(vf, pf, highest_unit, filter_forms, options)
+        }));
+    }
+    forms
+}
 
-        self.person = Some(HcPerson::from_i16(person.try_into().unwrap()));
-        self.number = Some(HcNumber::from_i16(number.try_into().unwrap()));
-        self.tense = HcTense::from_i16(tense.try_into().unwrap());
-        self.mood = HcMood::from_i16(mood.try_into().unwrap());
-        self.voice = HcVoice::from_i16(voice.try_into().unwrap());
+// same as random_form_with_options, but also returns every candidate that was turned down and why
+pub fn random_form_traced<R: Rng + ?Sized>(
+    vf: &HcGreekVerbForm,
+    num_changes: u8,
+    highest_unit: Option<i16>,
+    parameters: &VerbParameters,
+    filter_forms: Option<&HashSet<u32>>,
+    options: &RandomFormOptions,
+    rng: &mut R,
+) -> (Result<RandomForm, ChooserError>, Vec<RejectedCandidate>) {
+    let mut trace = vec![];
+    let (res, diag) = choose_random_form(
+        vf,
+        num_changes,
+        highest_unit,
+        parameters,
+        filter_forms,
+        options,
+        rng,
+        Some(&mut trace),
+    );
+    let res = res.map(|pf| {
+        let changes = param_changes(vf, &pf);
+        (pf, diag, changes)
+    });
+    (res, trace)
+}
 
-        //(person, number, tense, mood, voice)
+// the params change_params can change in vf, after setting the params with only one allowed
+// value: not frozen, with a weight and with another allowed value; None if vf doesn't fit its mood
+fn changeable_params(
+    vf: &mut HcGreekVerbForm,
+    parameters: &VerbParameters,
+    options: &RandomFormOptions,
+) -> Option<Vec<FormParam>> {
+    let mut possible_params = fix_single_value_params(vf, parameters, &options.participles);
+    if !fits_mood(vf) {
+        return None;
     }
+    let weights = &options.weights;
+    possible_params.retain(|p| {
+        !options.frozen.contains(p)
+            && weights.weight(p) > 0.0
+            && weights.can_change(vf, p, parameters)
+    });
+    Some(possible_params)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::sync::Arc;
+// params with only one allowed value are set to that value and can't be changed
+// returns the params that are left to change
+fn fix_single_value_params(
+    vf: &mut HcGreekVerbForm,
+    parameters: &VerbParameters,
+    participles: &ParticipleParameters,
+) -> Vec<FormParam> {
+    let mut fixed = vec![];
+
+    // first, as the mood decides which of the other params the form has
+    if parameters.moods.len() == 1 {
+        let mood = parameters.moods[0];
+        match forms_in_mood(vf, mood, parameters, participles)
+            .into_iter()
+            .next()
+        {
+            Some(form) => *vf = form,
+            None => vf.mood = mood,
+        }
+        fixed.push(HcParameters::Mood.into());
+    }
+    match vf.mood {
+        HcMood::Infinitive => {}
+        HcMood::Participle => {
+            if participles.genders.len() == 1 {
+                vf.gender = Some(participles.genders[0]);
+            }
+            if participles.cases.len() == 1 {
+                vf.case = Some(participles.cases[0]);
+            }
+            if participles.genders.len() * participles.cases.len() <= 1 {
+                fixed.push(FormParam::Declension);
+            }
+        }
+        _ => {
+            if parameters.persons.len() == 1 {
+                vf.person = Some(parameters.persons[0]);
+                fixed.push(HcParameters::Person.into());
+            }
+        }
+    }
+    if parameters.numbers.len() == 1 {
+        if vf.mood != HcMood::Infinitive {
+            vf.number = Some(parameters.numbers[0]);
+        }
+        fixed.push(HcParameters::Number.into());
+    }
+    if parameters.tenses.len() == 1 {
+        vf.tense = parameters.tenses[0];
+        fixed.push(HcParameters::Tense.into());
+    }
+    if parameters.voices.len() == 1 {
+        vf.voice = parameters.voices[0];
+        fixed.push(HcParameters::Voice.into());
+    }
 
-    #[test]
-    fn test_param_hash() {
-        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
-        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
-        let a = HcGreekVerbForm {
-            verb: verb.clone(),
-            person: Some(HcPerson::First),
-            number: Some(HcNumber::Singular),
-            tense: HcTense::Future,
-            voice: HcVoice::Active,
-            mood: HcMood::Indicative,
-            gender: None,
-            case: None,
-        };
+    // a participle's declension takes the place of the person it doesn't have
+    let person = match vf.mood {
+        HcMood::Participle => FormParam::Declension,
+        _ => HcParameters::Person.into(),
+    };
+    let mut possible_params = vec![
+        person,
+        HcParameters::Number.into(),
+        HcParameters::Tense.into(),
+        HcParameters::Mood.into(),
+        HcParameters::Voice.into(),
+    ];
+    possible_params.retain(|p| has_param(vf, p) && !fixed.contains(p));
+    possible_params
+}
 
-        //b has different params from a
-        let mut b = HcGreekVerbForm {
-            verb: verb.clone(),
-            person: Some(HcPerson::Third),
-            number: Some(HcNumber::Plural),
-            tense: HcTense::Present,
-            voice: HcVoice::Passive,
-            mood: HcMood::Imperative,
-            gender: None,
-            case: None,
-        };
+pub(crate) fn is_finite(mood: HcMood) -> bool {
+    !matches!(mood, HcMood::Infinitive | HcMood::Participle)
+}
 
-        let hash = a.param_hash();
-        b.extract_params_from_hash(hash); //this should set b's params to equal a: thus the forms are equal
-                                          //test round trip to param hash to form again
-        assert_eq!(a, b);
+// a finite form has a person and number, an infinitive has neither, and a participle has a
+// number, gender and case
+fn fits_mood(vf: &HcGreekVerbForm) -> bool {
+    match vf.mood {
+        HcMood::Infinitive => {
+            vf.person.is_none() && vf.number.is_none() && vf.gender.is_none() && vf.case.is_none()
+        }
+        HcMood::Participle => {
+            vf.person.is_none() && vf.number.is_some() && vf.gender.is_some() && vf.case.is_some()
+        }
+        _ => vf.person.is_some() && vf.number.is_some(),
     }
-    /*
-        #[test]
-        fn test_oida() {
-            // All elements can be initialized to the same value.
-            let mut results: [usize; 432] = [0; 432];
+}
 
-            let oida = "οἶδα, εἴσομαι, —, —, —, —";
-            let verb = Arc::new(HcGreekVerb::from_string(1, oida, REGULAR, 0).unwrap());
-            let a = HcGreekVerbForm {
-                verb: verb.clone(),
-                person: Some(HcPerson::First),
-                number: Some(HcNumber::Singular),
-                tense: HcTense::Perfect,
-                voice: HcVoice::Active,
-                mood: HcMood::Indicative,
-                gender: None,
-                case: None,
-            };
+// false for the params the form's mood doesn't have: a person outside the finite moods, a
+// number in the infinitive and a declension outside the participle
+fn has_param(vf: &HcGreekVerbForm, param: &FormParam) -> bool {
+    match param {
+        FormParam::Param(HcParameters::Person) => is_finite(vf.mood),
+        FormParam::Param(HcParameters::Number) => vf.mood != HcMood::Infinitive,
+        FormParam::Declension => vf.mood == HcMood::Participle,
+        _ => true,
+    }
+}
 
-            let first_hash = a.param_hash() as usize;
-            let mut idx: usize = 0;
+// every form made by moving vf to mood: person, number, gender and case are dropped if the
+// mood has none, kept if vf has them already, and otherwise take each allowed value
+fn forms_in_mood(
+    vf: &HcGreekVerbForm,
+    mood: HcMood,
+    parameters: &VerbParameters,
+    participles: &ParticipleParameters,
+) -> Vec<HcGreekVerbForm> {
+    fn values<T: Copy>(has: bool, current: Option<T>, allowed: &[T]) -> Vec<Option<T>> {
+        match (has, current) {
+            (false, _) => vec![None],
+            (true, Some(_)) => vec![current],
+            (true, None) => allowed.iter().map(|x| Some(*x)).collect(),
+        }
+    }
+    let persons = values(is_finite(mood), vf.person, &parameters.persons);
+    let numbers = values(mood != HcMood::Infinitive, vf.number, &parameters.numbers);
+    let genders = values(mood == HcMood::Participle, vf.gender, &participles.genders);
+    let cases = values(mood == HcMood::Participle, vf.case, &participles.cases);
+
+    let mut forms = vec![];
+    for person in &persons {
+        for number in &numbers {
+            for gender in &genders {
+                for case in &cases {
+                    forms.push(HcGreekVerbForm {
+                        mood,
+                        person: *person,
+                        number: *number,
+                        gender: *gender,
+                        case: *case,
+                        ..vf.clone()
+                    });
+                }
+            }
+        }
+    }
+    forms
+}
 
-            let max_changes = 2;
-            let highest_unit = 20;
-            let verb_params = VerbParameters {
-                persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+// the participle in each other allowed gender and case
+fn declension_alternatives(
+    vf: &HcGreekVerbForm,
+    participles: &ParticipleParameters,
+) -> Vec<HcGreekVerbForm> {
+    let mut forms = vec![];
+    for gender in &participles.genders {
+        for case in &participles.cases {
+            if (Some(*gender), Some(*case)) != (vf.gender, vf.case) {
+                forms.push(HcGreekVerbForm {
+                    gender: Some(*gender),
+                    case: Some(*case),
+                    ..vf.clone()
+                });
+            }
+        }
+    }
+    forms
+}
+
+/// Every form of the verb that the parameters, unit and curriculum allow, in the order their
+/// values are listed: by mood, then tense, voice, person, number, gender and case. The first
+/// is a start form inside the allowed set.
+pub fn allowed_forms(
+    verb: &Arc<HcGreekVerb>,
+    highest_unit: Option<i16>,
+    parameters: &VerbParameters,
+    options: &RandomFormOptions,
+) -> Vec<HcGreekVerbForm> {
+    let mut forms = vec![];
+    for mood in &parameters.moods {
+        for tense in &parameters.tenses {
+            for voice in &parameters.voices {
+                let vf = HcGreekVerbForm {
+                    verb: verb.clone(),
+                    person: None,
+                    number: None,
+                    tense: *tense,
+                    voice: *voice,
+                    mood: *mood,
+                    gender: None,
+                    case: None,
+                };
+                forms.extend(
+                    forms_in_mood(&vf, *mood, parameters, &options.participles)
+                        .into_iter()
+                        .filter(|pf| is_acceptable_form(pf, pf, highest_unit, None, options)),
+                );
+            }
+        }
+    }
+    forms
+}
+
+/// The params that differ between two forms, with their values. A param only one of the
+/// forms' moods has changes from or to `None`, e.g. the person and number going to the infinitive.
+pub fn param_changes(from: &HcGreekVerbForm, to: &HcGreekVerbForm) -> Vec<ParamChange> {
+    let values = |vf: &HcGreekVerbForm| {
+        [
+            vf.person.map(ParamValue::Person),
+            vf.number.map(ParamValue::Number),
+            Some(ParamValue::Tense(vf.tense)),
+            Some(ParamValue::Mood(vf.mood)),
+            Some(ParamValue::Voice(vf.voice)),
+            vf.gender
+                .zip(vf.case)
+                .map(|(g, c)| ParamValue::Declension(g, c)),
+        ]
+    };
+    FormParam::ALL
+        .into_iter()
+        .zip(values(from).into_iter().zip(values(to)))
+        .filter(|(_, (old, new))| old != new)
+        .map(|(param, (old, new))| ParamChange { param, old, new })
+        .collect()
+}
+
+/// Just the params of `param_changes`.
+pub fn changed_params(from: &HcGreekVerbForm, to: &HcGreekVerbForm) -> Vec<FormParam> {
+    param_changes(from, to)
+        .into_iter()
+        .map(|c| c.param)
+        .collect()
+}
+
+// every form made by changing param to one of its other allowed values
+pub(crate) fn alternative_values(
+    vf: &HcGreekVerbForm,
+    param: &FormParam,
+    parameters: &VerbParameters,
+    options: &RandomFormOptions,
+) -> Vec<HcGreekVerbForm> {
+    let weights = &options.weights;
+    if !has_param(vf, param) {
+        return vec![];
+    }
+    let FormParam::Param(param) = param else {
+        return declension_alternatives(vf, &options.participles);
+    };
+    match param {
+        HcParameters::Person => parameters
+            .persons
+            .iter()
+            .filter(|x| Some(**x) != vf.person && value_weight(&weights.persons, x) > 0.0)
+            .map(|x| HcGreekVerbForm {
+                person: Some(*x),
+                ..vf.clone()
+            })
+            .collect(),
+        HcParameters::Number => parameters
+            .numbers
+            .iter()
+            .filter(|x| Some(**x) != vf.number && value_weight(&weights.numbers, x) > 0.0)
+            .map(|x| HcGreekVerbForm {
+                number: Some(*x),
+                ..vf.clone()
+            })
+            .collect(),
+        HcParameters::Tense => parameters
+            .tenses
+            .iter()
+            .filter(|x| **x != vf.tense && value_weight(&weights.tenses, x) > 0.0)
+            .map(|x| HcGreekVerbForm {
+                tense: *x,
+                ..vf.clone()
+            })
+            .collect(),
+        HcParameters::Mood => parameters
+            .moods
+            .iter()
+            .filter(|x| **x != vf.mood && value_weight(&weights.moods, x) > 0.0)
+            .flat_map(|x| forms_in_mood(vf, *x, parameters, &options.participles))
+            .collect(),
+        HcParameters::Voice => parameters
+            .voices
+            .iter()
+            .filter(|x| **x != vf.voice && value_weight(&weights.voices, x) > 0.0)
+            .map(|x| HcGreekVerbForm {
+                voice: *x,
+                ..vf.clone()
+            })
+            .collect(),
+    }
+}
+
+// same checks as random_form: a real form, allowed after vf, in the unit and not filtered
+fn is_acceptable_form(
+    vf: &HcGreekVerbForm,
+    pf: &HcGreekVerbForm,
+    highest_unit: Option<i16>,
+    filter_forms: Option<&HashSet<u32>>,
+    options: &RandomFormOptions,
+) -> bool {
+    match pf.get_form(false) {
+        Ok(res) => {
+            res.last().unwrap().form != "—"
+                && verbs::has_tense_voice(&pf.verb, pf.tense, pf.voice)
+                && !vf.block_middle_passive(pf)
+                && !options.curriculum().blocks(pf, highest_unit)
+                && !filter_forms.is_some_and(|f| f.contains(&pf.param_hash()))
+        }
+        Err(_e) => false,
+    }
+}
+
+// the retry loop shared by random_form_with_options and random_form_traced
+#[allow(clippy::too_many_arguments)]
+fn choose_random_form<R: Rng + ?Sized>(
+    vf: &HcGreekVerbForm,
+    num_changes: u8,
+    highest_unit: Option<i16>,
+    parameters: &VerbParameters,
+    filter_forms: Option<&HashSet<u32>>, //previously used forms we don't want to return
+    options: &RandomFormOptions,
+    rng: &mut R,
+    mut trace: Option<&mut Vec<RejectedCandidate>>,
+) -> (Result<HcGreekVerbForm, ChooserError>, Diagnostics) {
+    let budget = &options.budget;
+    let curriculum = options.curriculum();
+    let mut diag = Diagnostics {
+        dash: 0,
+        middle_passive: 0,
+        blocked_for_unit: 0,
+        filtered: 0,
+        illegal: 0,
+    };
+
+    // don't try tenses and voices the principal parts have no forms for
+    let parameters = &verbs::available_parameters(&vf.verb, parameters);
+    if parameters.tenses.is_empty() || parameters.voices.is_empty() {
+        return (Err(ChooserError::NoFormsForParameters), diag);
+    }
+    // fail early when there aren't enough params to change
+    if options.exact_changes {
+        let possible =
+            changeable_params(&mut vf.clone(), parameters, options).map_or(0, |p| p.len());
+        if possible < num_changes.into() {
+            let possible = possible as u8;
+            return (
+                Err(ChooserError::TooFewChanges {
+                    requested: num_changes,
+                    possible,
+                }),
+                diag,
+            );
+        }
+    }
+    // used to say why we gave up
+    let mut found_real_form = false;
+    let mut found_form_in_unit = false;
+    let mut most_changes = 0;
+
+    for num_tries in 0..budget.max_tries {
+        let ignore_filter = budget.ignore_filter_after.is_some_and(|n| num_tries >= n);
+
+        let mut pf = vf.clone();
+        let changed = pf.change_params_with_options(num_changes, parameters, options, rng);
+        // e.g. a person that was to change after a change to the infinitive
+        most_changes = most_changes.max(changed.len());
+        if options.exact_changes && changed.len() < num_changes.into() {
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.push(RejectedCandidate {
+                    result: pf
+                        .get_form(false)
+                        .map(|res| res.last().unwrap().form.clone())
+                        .map_err(|e| format!("{:?}", e)),
+                    form: pf,
+                    reasons: vec![RejectionReason::TooFewChanges],
+                });
+            }
+            continue;
+        }
+        match pf.get_form(false) {
+            Ok(res) => {
+                // or a tense and voice the principal parts have no forms for together
+                let is_dash = res.last().unwrap().form == "—"
+                    || !verbs::has_tense_voice(&pf.verb, pf.tense, pf.voice);
+                let middle_passive = vf.block_middle_passive(&pf);
+                let blocked_for_unit = curriculum.blocks(&pf, highest_unit);
+                let filtered =
+                    !ignore_filter && filter_forms.is_some_and(|f| f.contains(&pf.param_hash()));
+
+                if !is_dash {
+                    found_real_form = true;
+                    if !blocked_for_unit {
+                        found_form_in_unit = true;
+                    }
+                }
+
+                if middle_passive {
+                    diag.middle_passive += 1;
+                } else if blocked_for_unit {
+                    diag.blocked_for_unit += 1;
+                } else if filtered {
+                    diag.filtered += 1;
+                } else if is_dash {
+                    diag.dash += 1;
+                } else {
+                    return (Ok(pf), diag);
+                }
+
+                // diag counts each rejection once, the trace keeps every reason that applied
+                if let Some(trace) = trace.as_deref_mut() {
+                    let mut reasons = vec![];
+                    if is_dash {
+                        reasons.push(RejectionReason::Dash);
+                    }
+                    if middle_passive {
+                        reasons.push(RejectionReason::MiddlePassive);
+                    }
+                    if blocked_for_unit {
+                        reasons.extend(
+                            curriculum
+                                .block_reasons(&pf, highest_unit)
+                                .into_iter()
+                                .map(RejectionReason::BlockedForUnit),
+                        );
+                    }
+                    if filtered {
+                        reasons.push(RejectionReason::Filtered);
+                    }
+                    trace.push(RejectedCandidate {
+                        result: Ok(res.last().unwrap().form.clone()),
+                        form: pf,
+                        reasons,
+                    });
+                }
+            } //only 3rd pl consonant stem perfects/pluperfects return - now
+            Err(e) => {
+                diag.illegal += 1;
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.push(RejectedCandidate {
+                        form: pf,
+                        result: Err(format!("{:?}", e)),
+                        reasons: vec![RejectionReason::Illegal],
+                    });
+                }
+            }
+        }
+    }
+
+    let err = match highest_unit {
+        _ if options.exact_changes && most_changes < num_changes.into() => {
+            ChooserError::TooFewChanges {
+                requested: num_changes,
+                possible: most_changes as u8,
+            }
+        }
+        _ if !found_real_form => ChooserError::NoFormsForParameters,
+        Some(unit) if !found_form_in_unit => ChooserError::UnitBlocksEverything(unit),
+        _ => ChooserError::FilterExhausted,
+    };
+    (Err(err), diag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_param_hash() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Future,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+
+        //b has different params from a
+        let mut b = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::Third),
+            number: Some(HcNumber::Plural),
+            tense: HcTense::Present,
+            voice: HcVoice::Passive,
+            mood: HcMood::Imperative,
+            gender: None,
+            case: None,
+        };
+
+        let hash = a.param_hash();
+        try_extract_params_from_hash(&mut b, hash).unwrap(); //this should set b's params to equal a: thus the forms are equal
+                                                             //test round trip to param hash to form again
+        assert_eq!(a, b);
+
+        // non-finite forms no longer collide with finite cells, and also round trip
+        let infinitive = HcGreekVerbForm {
+            person: None,
+            number: None,
+            mood: HcMood::Infinitive,
+            ..a.clone()
+        };
+        let hash = infinitive.param_hash();
+        assert!(hash >= lookup::NUM_CELLS);
+        try_extract_params_from_hash(&mut b, hash).unwrap();
+        assert_eq!(b, infinitive);
+        try_extract_params_from_hash(&mut b, a.param_hash()).unwrap();
+        assert_eq!(a, b);
+
+        // neither a finite cell nor a form id
+        assert_eq!(
+            try_extract_params_from_hash(&mut b, lookup::NUM_CELLS),
+            Err(FormIdError::UnknownVersion(0))
+        );
+        assert_eq!(a, b);
+
+        let object: &dyn GetRandom = &a;
+        assert_eq!(object.param_hash(), a.param_hash());
+    }
+    /*
+        #[test]
+        fn test_oida() {
+            // All elements can be initialized to the same value.
+            let mut results: [usize; 432] = [0; 432];
+
+            let oida = "οἶδα, εἴσομαι, —, —, —, —";
+            let verb = Arc::new(HcGreekVerb::from_string(1, oida, REGULAR, 0).unwrap());
+            let a = HcGreekVerbForm {
+                verb: verb.clone(),
+                person: Some(HcPerson::First),
+                number: Some(HcNumber::Singular),
+                tense: HcTense::Perfect,
+                voice: HcVoice::Active,
+                mood: HcMood::Indicative,
+                gender: None,
+                case: None,
+            };
+
+            let first_hash = a.param_hash() as usize;
+            let mut idx: usize = 0;
+
+            let max_changes = 2;
+            let highest_unit = 20;
+            let verb_params = VerbParameters {
+                persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
                 numbers: vec![HcNumber::Singular, HcNumber::Plural],
                 tenses: vec![
                     HcTense::Present,
@@ -529,34 +1285,637 @@ mod tests {
 
             //println!("count {} {:?}", results[b.param_hash() as usize], m);
             for i in m {
-                b.extract_params_from_hash(i.0.try_into().unwrap());
+                try_extract_params_from_hash(&mut b, i.0.try_into().unwrap()).unwrap();
                 println!("{} {:?}", i.0, b);
             }
-            assert!(
-                results[b.param_hash() as usize] / count > 30 / count
-                    && results[b.param_hash() as usize] / count < 34 / count,
-                "b.param_hash() {}",
-                results[b.param_hash() as usize] / count
-            );
+            assert!(
+                results[b.param_hash() as usize] / count > 30 / count
+                    && results[b.param_hash() as usize] / count < 34 / count,
+                "b.param_hash() {}",
+                results[b.param_hash() as usize] / count
+            );
+        }
+    */
+    const SEED: u64 = 1_000_003;
+
+    #[test]
+    fn test_random_form_seeded_is_repeatable() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+
+        let verb_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![
+                HcTense::Present,
+                HcTense::Imperfect,
+                HcTense::Future,
+                HcTense::Aorist,
+                HcTense::Perfect,
+                HcTense::Pluperfect,
+            ],
+            voices: vec![HcVoice::Active, HcVoice::Middle, HcVoice::Passive],
+            moods: vec![
+                HcMood::Indicative,
+                HcMood::Subjunctive,
+                HcMood::Optative,
+                HcMood::Imperative,
+            ],
+        };
+
+        // the same seed must give the same drill sequence
+        let mut rng1 = StdRng::seed_from_u64(SEED);
+        let mut rng2 = StdRng::seed_from_u64(SEED);
+        let mut prev1 = a.clone();
+        let mut prev2 = a;
+        for _i in 0..100 {
+            (prev1, _, _) = prev1
+                .random_form_with_options(
+                    2,
+                    Some(20),
+                    &verb_params,
+                    None,
+                    &RandomFormOptions::default(),
+                    &mut rng1,
+                )
+                .unwrap();
+            (prev2, _, _) = prev2
+                .random_form_with_options(
+                    2,
+                    Some(20),
+                    &verb_params,
+                    None,
+                    &RandomFormOptions::default(),
+                    &mut rng2,
+                )
+                .unwrap();
+            assert_eq!(prev1, prev2);
+        }
+    }
+
+    #[test]
+    fn test_random2() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Future,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+
+        let max_changes = 2;
+        let highest_unit = 2;
+        let verb_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![
+                HcTense::Present,
+                HcTense::Imperfect,
+                HcTense::Future,
+                HcTense::Aorist,
+                HcTense::Perfect,
+                HcTense::Pluperfect,
+            ],
+            voices: vec![HcVoice::Active, HcVoice::Middle, HcVoice::Passive],
+            moods: vec![
+                HcMood::Indicative,
+                HcMood::Subjunctive,
+                HcMood::Optative,
+                HcMood::Imperative,
+            ],
+        };
+
+        let mut form_filter = HashSet::new();
+        // form_filter.insert(b.param_hash());
+        // form_filter.insert(c.param_hash());
+
+        for _i in 0..10 {
+            let (d, _diag) = a.random_form(
+                max_changes,
+                Some(highest_unit),
+                &verb_params,
+                Some(&form_filter),
+            );
+            form_filter.insert(d.param_hash());
+            //assert!(!form_filter.contains(&d.param_hash()));
+            //assert_ne!(d.param_hash(), c.param_hash()); //the random form should never equal c because c was added to filter HashSet
+        }
+    }
+
+    #[test]
+    fn test_random() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Future,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let b = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::Second),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Future,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let c = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::Third),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Future,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+
+        let max_changes = 1;
+        let highest_unit = 2;
+        let verb_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present, HcTense::Imperfect],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Indicative],
+        };
+
+        let mut form_filter = HashSet::new();
+        form_filter.insert(b.param_hash());
+        form_filter.insert(c.param_hash());
+
+        for _i in 0..10_000 {
+            let (d, _diag) = a.random_form(
+                max_changes,
+                Some(highest_unit),
+                &verb_params,
+                Some(&form_filter),
+            );
+            assert!(!form_filter.contains(&d.param_hash()));
+            assert_ne!(d.param_hash(), c.param_hash()); //the random form should never equal c because c was added to filter HashSet
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "unit 2 blocks every form")]
+    fn test_random_form_panics_without_forms() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        // only middle forms, which unit 2 doesn't allow
+        let middle_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present, HcTense::Imperfect],
+            voices: vec![HcVoice::Middle],
+            moods: vec![HcMood::Indicative],
+        };
+        a.random_form(1, Some(2), &middle_params, None);
+    }
+
+    #[test]
+    fn test_try_random_form_errors() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let b = HcGreekVerbForm {
+            person: Some(HcPerson::Second),
+            ..a.clone()
+        };
+        let c = HcGreekVerbForm {
+            person: Some(HcPerson::Third),
+            ..a.clone()
+        };
+
+        let verb_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular],
+            tenses: vec![HcTense::Present],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Indicative],
+        };
+        let mut form_filter = HashSet::new();
+        form_filter.insert(b.param_hash());
+        form_filter.insert(c.param_hash());
+
+        // never fall back to ignoring the filter: nothing is left
+        let strict_options = RandomFormOptions {
+            budget: RetryBudget {
+                ignore_filter_after: None,
+                max_tries: 100,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            a.random_form_with_options(
+                1,
+                Some(2),
+                &verb_params,
+                Some(&form_filter),
+                &strict_options,
+                &mut rand::thread_rng()
+            )
+            .err(),
+            Some(ChooserError::FilterExhausted)
+        );
+        // the default budget falls back to ignoring the filter
+        assert!(a
+            .random_form_with_options(
+                1,
+                Some(2),
+                &verb_params,
+                Some(&form_filter),
+                &RandomFormOptions::default(),
+                &mut rand::thread_rng()
+            )
+            .is_ok());
+
+        // only middle forms, which unit 2 doesn't allow
+        let middle_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present, HcTense::Imperfect],
+            voices: vec![HcVoice::Middle],
+            moods: vec![HcMood::Indicative],
+        };
+        assert_eq!(
+            a.random_form_with_options(
+                1,
+                Some(2),
+                &middle_params,
+                None,
+                &RandomFormOptions::default(),
+                &mut rand::thread_rng()
+            )
+            .err(),
+            Some(ChooserError::UnitBlocksEverything(2))
+        );
+
+        // oida has no aorist
+        let oida = "οἶδα, εἴσομαι, —, —, —, —";
+        let oida_verb = Arc::new(HcGreekVerb::from_string(1, oida, REGULAR, 0).unwrap());
+        let d = HcGreekVerbForm {
+            verb: oida_verb,
+            ..a.clone()
+        };
+        let aorist_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Aorist],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Indicative],
+        };
+        assert_eq!(
+            d.random_form_with_options(
+                1,
+                None,
+                &aorist_params,
+                None,
+                &RandomFormOptions::default(),
+                &mut rand::thread_rng()
+            )
+            .err(),
+            Some(ChooserError::NoFormsForParameters)
+        );
+    }
+
+    #[test]
+    fn test_try_random_form_traced() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        // only the future optative is allowed to change to, which unit 4 blocks
+        let verb_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular],
+            tenses: vec![HcTense::Future],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Optative],
+        };
+        let options = RandomFormOptions {
+            budget: RetryBudget {
+                ignore_filter_after: None,
+                max_tries: 20,
+            },
+            ..Default::default()
+        };
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let (res, trace) =
+            random_form_traced(&a, 1, Some(4), &verb_params, None, &options, &mut rng);
+        assert_eq!(res.err(), Some(ChooserError::UnitBlocksEverything(4)));
+        assert_eq!(trace.len(), 20);
+        for t in trace {
+            assert!(t.result.is_ok());
+            assert_eq!(t.form.tense, HcTense::Future);
+            assert_eq!(
+                t.reasons,
+                vec![RejectionReason::BlockedForUnit(BlockReason::FutureOptative)]
+            );
+        }
+
+        // with a filter on every other person, the trace shows both reasons
+        let mut form_filter = HashSet::new();
+        for p in [HcPerson::Second, HcPerson::Third] {
+            form_filter.insert(
+                HcGreekVerbForm {
+                    person: Some(p),
+                    tense: HcTense::Future,
+                    mood: HcMood::Optative,
+                    ..a.clone()
+                }
+                .param_hash(),
+            );
+        }
+        let (_res, trace) = random_form_traced(
+            &a,
+            1,
+            Some(4),
+            &verb_params,
+            Some(&form_filter),
+            &options,
+            &mut rng,
+        );
+        assert!(trace.iter().all(|t| t.reasons
+            == vec![
+                RejectionReason::BlockedForUnit(BlockReason::FutureOptative),
+                RejectionReason::Filtered
+            ]));
+    }
+
+    #[test]
+    fn test_reachable_forms() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let verb_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Indicative],
+        };
+
+        // one change: 2nd sg, 3rd sg, 1st pl
+        let forms = reachable_forms(
+            &a,
+            1,
+            Some(2),
+            &verb_params,
+            None,
+            &RandomFormOptions::default(),
+        );
+        assert_eq!(forms.len(), 3);
+        assert!(forms.iter().all(|f| f.tense == HcTense::Present));
+
+        // two changes: 2nd pl, 3rd pl
+        let forms = reachable_forms(
+            &a,
+            2,
+            Some(2),
+            &verb_params,
+            None,
+            &RandomFormOptions::default(),
+        );
+        assert_eq!(forms.len(), 2);
+        assert!(forms
+            .iter()
+            .all(|f| f.number == Some(HcNumber::Plural) && f.person != Some(HcPerson::First)));
+
+        // filtered forms are left out
+        let mut form_filter = HashSet::new();
+        form_filter.insert(forms[0].param_hash());
+        let filtered = reachable_forms(
+            &a,
+            2,
+            Some(2),
+            &verb_params,
+            Some(&form_filter),
+            &RandomFormOptions::default(),
+        );
+        assert_eq!(filtered, vec![forms[1].clone()]);
+
+        // every form random_form picks is in the list
+        let all_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![
+                HcTense::Present,
+                HcTense::Imperfect,
+                HcTense::Future,
+                HcTense::Aorist,
+                HcTense::Perfect,
+                HcTense::Pluperfect,
+            ],
+            voices: vec![HcVoice::Active, HcVoice::Middle, HcVoice::Passive],
+            moods: vec![
+                HcMood::Indicative,
+                HcMood::Subjunctive,
+                HcMood::Optative,
+                HcMood::Imperative,
+            ],
+        };
+        let forms = reachable_forms(
+            &a,
+            2,
+            Some(20),
+            &all_params,
+            None,
+            &RandomFormOptions::default(),
+        );
+        let mut rng = StdRng::seed_from_u64(SEED);
+        for _i in 0..100 {
+            let (b, _diag, _) = a
+                .random_form_with_options(
+                    2,
+                    Some(20),
+                    &all_params,
+                    None,
+                    &RandomFormOptions::default(),
+                    &mut rng,
+                )
+                .unwrap();
+            assert!(forms.contains(&b));
+        }
+
+        // oida has no aorist: nothing to choose from
+        let oida = "οἶδα, εἴσομαι, —, —, —, —";
+        let oida_verb = Arc::new(HcGreekVerb::from_string(1, oida, REGULAR, 0).unwrap());
+        let d = HcGreekVerbForm {
+            verb: oida_verb,
+            ..a
+        };
+        let aorist_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Aorist],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Indicative],
+        };
+        assert!(reachable_forms(
+            &d,
+            1,
+            None,
+            &aorist_params,
+            None,
+            &RandomFormOptions::default()
+        )
+        .is_empty());
+    }
+
+    // change_params as it was before weights, with the rng passed in: the shuffled params are
+    // changed in turn, each to another allowed value chosen uniformly
+    fn baseline_change_params<R: Rng>(
+        vf: &mut HcGreekVerbForm,
+        n_params_to_change: u8,
+        parameters: &VerbParameters,
+        rng: &mut R,
+    ) -> Vec<HcParameters> {
+        let mut possible_params = vec![
+            HcParameters::Person,
+            HcParameters::Number,
+            HcParameters::Tense,
+            HcParameters::Mood,
+            HcParameters::Voice,
+        ];
+        if parameters.persons.len() == 1 {
+            vf.person = Some(parameters.persons[0]);
+            possible_params.retain(|e| *e != HcParameters::Person);
+        }
+        if parameters.numbers.len() == 1 {
+            vf.number = Some(parameters.numbers[0]);
+            possible_params.retain(|e| *e != HcParameters::Number);
+        }
+        if parameters.tenses.len() == 1 {
+            vf.tense = parameters.tenses[0];
+            possible_params.retain(|e| *e != HcParameters::Tense);
+        }
+        if parameters.moods.len() == 1 {
+            vf.mood = parameters.moods[0];
+            possible_params.retain(|e| *e != HcParameters::Mood);
+        }
+        if parameters.voices.len() == 1 {
+            vf.voice = parameters.voices[0];
+            possible_params.retain(|e| *e != HcParameters::Voice);
+        }
+
+        possible_params.shuffle(rng);
+        possible_params.truncate(n_params_to_change.into());
+        for p in &possible_params {
+            match p {
+                HcParameters::Person => {
+                    let others: Vec<_> = parameters
+                        .persons
+                        .iter()
+                        .filter(|x| Some(**x) != vf.person)
+                        .collect();
+                    vf.person = Some(**others.choose(rng).unwrap());
+                }
+                HcParameters::Number => {
+                    let others: Vec<_> = parameters
+                        .numbers
+                        .iter()
+                        .filter(|x| Some(**x) != vf.number)
+                        .collect();
+                    vf.number = Some(**others.choose(rng).unwrap());
+                }
+                HcParameters::Tense => {
+                    let others: Vec<_> = parameters
+                        .tenses
+                        .iter()
+                        .filter(|x| **x != vf.tense)
+                        .collect();
+                    vf.tense = **others.choose(rng).unwrap();
+                }
+                HcParameters::Mood => {
+                    let others: Vec<_> =
+                        parameters.moods.iter().filter(|x| **x != vf.mood).collect();
+                    vf.mood = **others.choose(rng).unwrap();
+                }
+                HcParameters::Voice => {
+                    let others: Vec<_> = parameters
+                        .voices
+                        .iter()
+                        .filter(|x| **x != vf.voice)
+                        .collect();
+                    vf.voice = **others.choose(rng).unwrap();
+                }
+            }
         }
-    */
+        possible_params
+    }
+
     #[test]
-    fn test_random2() {
-        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+    fn test_weighted_change_params() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
         let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
         let a = HcGreekVerbForm {
-            verb: verb.clone(),
+            verb,
             person: Some(HcPerson::First),
             number: Some(HcNumber::Singular),
-            tense: HcTense::Future,
+            tense: HcTense::Present,
             voice: HcVoice::Active,
             mood: HcMood::Indicative,
             gender: None,
             case: None,
         };
-
-        let max_changes = 2;
-        let highest_unit = 2;
         let verb_params = VerbParameters {
             persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
             numbers: vec![HcNumber::Singular, HcNumber::Plural],
@@ -565,93 +1924,279 @@ mod tests {
                 HcTense::Imperfect,
                 HcTense::Future,
                 HcTense::Aorist,
-                HcTense::Perfect,
-                HcTense::Pluperfect,
             ],
             voices: vec![HcVoice::Active, HcVoice::Middle, HcVoice::Passive],
-            moods: vec![
-                HcMood::Indicative,
-                HcMood::Subjunctive,
-                HcMood::Optative,
-                HcMood::Imperative,
-            ],
+            moods: vec![HcMood::Indicative, HcMood::Subjunctive],
         };
 
-        let mut form_filter = HashSet::new();
-        // form_filter.insert(b.param_hash());
-        // form_filter.insert(c.param_hash());
+        // uniform weights change the params exactly as change_params did before weights
+        let uniform = RandomFormOptions {
+            weights: ParamWeights {
+                person: 2.0,
+                number: 2.0,
+                tense: 2.0,
+                mood: 2.0,
+                voice: 2.0,
+                declension: 2.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut rng1 = StdRng::seed_from_u64(SEED);
+        let mut rng2 = StdRng::seed_from_u64(SEED);
+        let mut b1 = a.clone();
+        let mut b2 = a.clone();
+        for _i in 0..1000 {
+            let c1 = b1.change_params_with_options(2, &verb_params, &uniform, &mut rng1);
+            let c2 = baseline_change_params(&mut b2, 2, &verb_params, &mut rng2);
+            assert_eq!(c1, c2.into_iter().map(FormParam::from).collect::<Vec<_>>());
+            assert_eq!(b1, b2);
+        }
 
-        for _i in 0..10 {
-            let (d, _diag) = a.random_form(
-                max_changes,
-                Some(highest_unit),
+        // change tense half the time, never to the aorist
+        let weights = ParamWeights {
+            tense: 4.0,
+            tenses: vec![(HcTense::Aorist, 0.0)],
+            ..Default::default()
+        };
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let count = 10_000;
+        let mut tense_changes = 0;
+        for _i in 0..count {
+            let mut b = a.clone();
+            let changed = b.change_params_with_options(
+                1,
                 &verb_params,
-                Some(&form_filter),
+                &RandomFormOptions {
+                    weights: weights.clone(),
+                    ..Default::default()
+                },
+                &mut rng,
             );
-            form_filter.insert(d.param_hash());
-            //assert!(!form_filter.contains(&d.param_hash()));
-            //assert_ne!(d.param_hash(), c.param_hash()); //the random form should never equal c because c was added to filter HashSet
+            if changed == vec![HcParameters::Tense.into()] {
+                tense_changes += 1;
+            }
+            assert_ne!(b.tense, HcTense::Aorist);
+        }
+        let share = tense_changes as f64 / count as f64;
+        assert!(share > 0.47 && share < 0.53, "{}", share);
+
+        // a param whose only other values have weight 0 is not changed
+        let weights = ParamWeights {
+            voices: vec![(HcVoice::Middle, 0.0), (HcVoice::Passive, 0.0)],
+            ..Default::default()
+        };
+        let options = RandomFormOptions {
+            weights: weights.clone(),
+            ..Default::default()
+        };
+        for _i in 0..100 {
+            let mut b = a.clone();
+            let changed = b.change_params_with_options(
+                1,
+                &verb_params,
+                &RandomFormOptions {
+                    weights: weights.clone(),
+                    ..Default::default()
+                },
+                &mut rng,
+            );
+            assert_eq!(changed.len(), 1);
+            assert_eq!(b.voice, HcVoice::Active);
+        }
+        assert!(reachable_forms(&a, 1, None, &verb_params, None, &options)
+            .iter()
+            .all(|f| f.voice == HcVoice::Active));
+
+        // weights that can't be sampled from fall back to a uniform choice
+        let weights = ParamWeights {
+            tense: f64::INFINITY,
+            mood: f64::MAX,
+            voice: f64::MAX,
+            tenses: vec![(HcTense::Future, f64::INFINITY)],
+            ..Default::default()
+        };
+        for _i in 0..100 {
+            let mut b = a.clone();
+            let changed = b.change_params_with_options(
+                2,
+                &verb_params,
+                &RandomFormOptions {
+                    weights: weights.clone(),
+                    ..Default::default()
+                },
+                &mut rng,
+            );
+            assert_eq!(changed.len(), 2);
+            assert_eq!(changed_params(&a, &b).len(), 2);
         }
     }
 
     #[test]
-    fn test_random() {
-        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
-        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+    fn test_random_form_narrowed_by_principal_parts() {
+        let oida = "οἶδα, εἴσομαι, —, —, —, —";
+        let verb = Arc::new(HcGreekVerb::from_string(1, oida, REGULAR, 0).unwrap());
         let a = HcGreekVerbForm {
-            verb: verb.clone(),
+            verb,
             person: Some(HcPerson::First),
             number: Some(HcNumber::Singular),
-            tense: HcTense::Future,
+            tense: HcTense::Perfect,
             voice: HcVoice::Active,
             mood: HcMood::Indicative,
             gender: None,
             case: None,
         };
-        let b = HcGreekVerbForm {
-            verb: verb.clone(),
-            person: Some(HcPerson::Second),
-            number: Some(HcNumber::Singular),
-            tense: HcTense::Future,
-            voice: HcVoice::Active,
-            mood: HcMood::Indicative,
-            gender: None,
-            case: None,
+        let verb_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![
+                HcTense::Present,
+                HcTense::Imperfect,
+                HcTense::Future,
+                HcTense::Aorist,
+                HcTense::Perfect,
+                HcTense::Pluperfect,
+            ],
+            voices: vec![HcVoice::Active, HcVoice::Middle, HcVoice::Passive],
+            moods: vec![HcMood::Indicative, HcMood::Subjunctive],
         };
-        let c = HcGreekVerbForm {
-            verb: verb.clone(),
-            person: Some(HcPerson::Third),
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let options = RandomFormOptions::default();
+        let mut prev = a.clone();
+        for _i in 0..100 {
+            let (pf, _, _) = prev
+                .random_form_with_options(2, None, &verb_params, None, &options, &mut rng)
+                .unwrap();
+            assert!(matches!(
+                pf.tense,
+                HcTense::Future | HcTense::Perfect | HcTense::Pluperfect
+            ));
+            assert_ne!(pf.voice, HcVoice::Passive);
+            // the future only in the middle, the perfect only in the active
+            assert!(verbs::has_tense_voice(&pf.verb, pf.tense, pf.voice));
+            prev = pf;
+        }
+        let reachable = reachable_forms(&a, 2, None, &verb_params, None, &options);
+        assert!(!reachable.is_empty());
+        assert!(reachable
+            .iter()
+            .all(|f| f.tense != HcTense::Present
+                && verbs::has_tense_voice(&f.verb, f.tense, f.voice)));
+
+        let aorist_only = VerbParameters {
+            tenses: vec![HcTense::Aorist],
+            ..verb_params
+        };
+        assert_eq!(
+            a.random_form_with_options(1, None, &aorist_only, None, &options, &mut rng)
+                .map(|(pf, _, _)| pf),
+            Err(ChooserError::NoFormsForParameters)
+        );
+    }
+
+    #[test]
+    fn test_non_finite_change_params() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let start = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
             number: Some(HcNumber::Singular),
-            tense: HcTense::Future,
+            tense: HcTense::Present,
             voice: HcVoice::Active,
             mood: HcMood::Indicative,
             gender: None,
             case: None,
         };
-
-        let max_changes = 1;
-        let highest_unit = 2;
-        let verb_params = VerbParameters {
+        let parameters = VerbParameters {
             persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
             numbers: vec![HcNumber::Singular, HcNumber::Plural],
-            tenses: vec![HcTense::Present, HcTense::Imperfect],
+            tenses: vec![HcTense::Present, HcTense::Aorist],
             voices: vec![HcVoice::Active],
-            moods: vec![HcMood::Indicative],
+            moods: vec![HcMood::Indicative, HcMood::Infinitive, HcMood::Participle],
         };
+        let options = RandomFormOptions::default();
+        let mut rng = StdRng::seed_from_u64(SEED);
+
+        // walk from form to form, through every mood and back
+        let mut vf = start.clone();
+        let mut moods_seen = vec![];
+        let mut declension_changes = 0;
+        for _i in 0..500 {
+            let prev = vf.clone();
+            let changed = vf.change_params_with_options(1, &parameters, &options, &mut rng);
+            assert_eq!(changed.len(), 1);
+            assert!(fits_mood(&vf), "{:?}", vf);
+            assert_ne!(vf, prev);
+            if prev.mood == HcMood::Participle && changed[0] == FormParam::Declension {
+                assert_eq!(vf.mood, HcMood::Participle);
+                assert!(vf.gender != prev.gender || vf.case != prev.case);
+                declension_changes += 1;
+            }
+            if !moods_seen.contains(&(prev.mood, vf.mood)) {
+                moods_seen.push((prev.mood, vf.mood));
+            }
+        }
+        assert!(declension_changes > 0);
+        for from in &parameters.moods {
+            for to in parameters.moods.iter().filter(|m| *m != from) {
+                assert!(moods_seen.contains(&(*from, *to)), "{:?} → {:?}", from, to);
+            }
+        }
 
-        let mut form_filter = HashSet::new();
-        form_filter.insert(b.param_hash());
-        form_filter.insert(c.param_hash());
-
-        for _i in 0..10_000 {
-            let (d, _diag) = a.random_form(
-                max_changes,
-                Some(highest_unit),
-                &verb_params,
-                Some(&form_filter),
+        // from a participle, one change reaches its other genders and cases, but no person
+        let participle = HcGreekVerbForm {
+            person: None,
+            mood: HcMood::Participle,
+            gender: Some(HcGender::Masculine),
+            case: Some(HcCase::Nominative),
+            ..start.clone()
+        };
+        let forms = reachable_forms(&participle, 1, None, &parameters, None, &options);
+        assert!(forms.iter().all(fits_mood));
+        let declined = forms
+            .iter()
+            .filter(|pf| pf.mood == HcMood::Participle && pf.tense == participle.tense)
+            .filter(|pf| pf.number == participle.number)
+            .count();
+        assert_eq!(declined, 3 * 4 - 1);
+
+        // non-finite forms are gated by unit like any other: only the indicative through unit 2
+        for _i in 0..50 {
+            let (pf, _, _) = start
+                .random_form_with_options(1, Some(2), &parameters, None, &options, &mut rng)
+                .unwrap();
+            assert_eq!(pf.mood, HcMood::Indicative);
+        }
+        let infinitive = HcGreekVerbForm {
+            person: None,
+            number: None,
+            mood: HcMood::Infinitive,
+            ..start.clone()
+        };
+        assert!(infinitive.block_for_hq_unit(Some(2)));
+        assert!(!infinitive.block_for_hq_unit(Some(20)));
+
+        // a param whose only value is listed twice has nothing to change to
+        let duplicated = VerbParameters {
+            tenses: vec![HcTense::Present, HcTense::Present],
+            voices: vec![HcVoice::Active, HcVoice::Active],
+            moods: vec![HcMood::Indicative, HcMood::Indicative],
+            ..parameters
+        };
+        for _i in 0..50 {
+            let mut vf = start.clone();
+            let changed = vf.change_params_with_options(
+                2,
+                &duplicated,
+                &RandomFormOptions::default(),
+                &mut rng,
             );
-            assert!(!form_filter.contains(&d.param_hash()));
-            assert_ne!(d.param_hash(), c.param_hash()); //the random form should never equal c because c was added to filter HashSet
+            assert!(changed
+                .iter()
+                .all(|p| *p == HcParameters::Person.into() || *p == HcParameters::Number.into()));
+            let actual = changed_params(&start, &vf);
+            assert!(actual.len() == changed.len() && actual.iter().all(|p| changed.contains(p)));
         }
     }
 
@@ -698,6 +2243,250 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_frozen_params_and_last_change() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let parameters = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present, HcTense::Future, HcTense::Aorist],
+            voices: vec![HcVoice::Active, HcVoice::Middle],
+            moods: vec![HcMood::Indicative, HcMood::Subjunctive],
+        };
+        let mut rng = StdRng::seed_from_u64(SEED);
+
+        // every listed param is left alone, not just one of them
+        for _i in 0..1000 {
+            let mut b = a.clone();
+            let changed = b.change_params(
+                2,
+                &parameters,
+                &mut [HcParameters::Person, HcParameters::Number],
+            );
+            assert_eq!(changed.len(), 2);
+            assert_eq!((b.person, b.number), (a.person, a.number));
+        }
+
+        let options = RandomFormOptions {
+            frozen: vec![HcParameters::Tense.into()],
+            ..Default::default()
+        };
+        for _i in 0..100 {
+            let (b, _, _) = a
+                .random_form_with_options(2, None, &parameters, None, &options, &mut rng)
+                .unwrap();
+            assert_eq!(b.tense, a.tense);
+        }
+        let forms = reachable_forms(&a, 2, None, &parameters, None, &options);
+        assert!(!forms.is_empty() && forms.iter().all(|b| b.tense == a.tense));
+
+        // the last change is only made again when there are too few other params
+        let options = RandomFormOptions {
+            last_change: vec![HcParameters::Person.into()],
+            avoid_last_change: true,
+            ..Default::default()
+        };
+        for _i in 0..100 {
+            let mut b = a.clone();
+            let changed = b.change_params_with_options(1, &parameters, &options, &mut rng);
+            assert!(changed.len() == 1 && changed[0] != HcParameters::Person.into());
+            assert_eq!(changed, changed_params(&a, &b));
+
+            let mut b = a.clone();
+            let changed = b.change_params_with_options(5, &parameters, &options, &mut rng);
+            assert_eq!(changed.len(), 5);
+        }
+        let forms = reachable_forms(&a, 1, None, &parameters, None, &options);
+        assert!(!forms.is_empty() && forms.iter().all(|b| b.person == a.person));
+        let forms = reachable_forms(&a, 5, None, &parameters, None, &options);
+        assert!(!forms.is_empty() && forms.iter().all(|b| b.person != a.person));
+    }
+
+    #[test]
+    fn test_param_changes_and_exact_changes() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        // only person, number and tense can change
+        let parameters = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present, HcTense::Aorist],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Indicative],
+        };
+        let mut rng = StdRng::seed_from_u64(SEED);
+
+        for _i in 0..100 {
+            let (b, _diag, changes) = a
+                .random_form_with_options(
+                    2,
+                    None,
+                    &parameters,
+                    None,
+                    &RandomFormOptions::default(),
+                    &mut rng,
+                )
+                .unwrap();
+            assert_eq!(changes.len(), 2);
+            for c in &changes {
+                match (c.param, c.old, c.new) {
+                    (
+                        FormParam::Param(HcParameters::Person),
+                        Some(ParamValue::Person(old)),
+                        Some(ParamValue::Person(new)),
+                    ) => {
+                        assert_eq!((Some(old), Some(new)), (a.person, b.person))
+                    }
+                    (
+                        FormParam::Param(HcParameters::Number),
+                        Some(ParamValue::Number(old)),
+                        Some(ParamValue::Number(new)),
+                    ) => {
+                        assert_eq!((Some(old), Some(new)), (a.number, b.number))
+                    }
+                    (
+                        FormParam::Param(HcParameters::Tense),
+                        Some(ParamValue::Tense(old)),
+                        Some(ParamValue::Tense(new)),
+                    ) => {
+                        assert_eq!((old, new), (a.tense, b.tense))
+                    }
+                    _ => panic!("unexpected change {:?}", c),
+                }
+            }
+        }
+
+        // asking for more changes than there are params: fewer changes, or an error
+        let (b, _diag, changes) = a
+            .random_form_with_options(
+                4,
+                None,
+                &parameters,
+                None,
+                &RandomFormOptions::default(),
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changed_params(&a, &b).len(), 3);
+        let exact = RandomFormOptions {
+            exact_changes: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            a.random_form_with_options(4, None, &parameters, None, &exact, &mut rng)
+                .map(|(pf, _, _)| pf),
+            Err(ChooserError::TooFewChanges {
+                requested: 4,
+                possible: 3
+            })
+        );
+        assert!(reachable_forms(&a, 4, None, &parameters, None, &exact).is_empty());
+        assert!(a
+            .random_form_with_options(3, None, &parameters, None, &exact, &mut rng)
+            .is_ok());
+
+        // a change to the infinitive leaves no person or number to change
+        let parameters = VerbParameters {
+            tenses: vec![HcTense::Present],
+            moods: vec![HcMood::Indicative, HcMood::Infinitive],
+            ..parameters
+        };
+        assert!(!reachable_forms(
+            &a,
+            3,
+            None,
+            &parameters,
+            None,
+            &RandomFormOptions::default()
+        )
+        .is_empty());
+        assert!(reachable_forms(&a, 3, None, &parameters, None, &exact).is_empty());
+        let (res, trace) = random_form_traced(&a, 3, None, &parameters, None, &exact, &mut rng);
+        assert_eq!(
+            res.map(|(pf, _, _)| pf),
+            Err(ChooserError::TooFewChanges {
+                requested: 3,
+                possible: 1
+            })
+        );
+        assert!(trace
+            .iter()
+            .all(|r| r.reasons == vec![RejectionReason::TooFewChanges]));
+
+        // a participle's gender and case change as its declension
+        let participle = HcGreekVerbForm {
+            person: None,
+            mood: HcMood::Participle,
+            gender: Some(HcGender::Masculine),
+            case: Some(HcCase::Nominative),
+            ..a.clone()
+        };
+        let declined = HcGreekVerbForm {
+            case: Some(HcCase::Genitive),
+            ..participle.clone()
+        };
+        assert_eq!(
+            param_changes(&participle, &declined),
+            vec![ParamChange {
+                param: FormParam::Declension,
+                old: Some(ParamValue::Declension(
+                    HcGender::Masculine,
+                    HcCase::Nominative
+                )),
+                new: Some(ParamValue::Declension(
+                    HcGender::Masculine,
+                    HcCase::Genitive
+                )),
+            }]
+        );
+        // and a change of mood shows the params that appear and vanish with it
+        assert_eq!(
+            param_changes(&a, &participle),
+            vec![
+                ParamChange {
+                    param: HcParameters::Person.into(),
+                    old: Some(ParamValue::Person(HcPerson::First)),
+                    new: None,
+                },
+                ParamChange {
+                    param: HcParameters::Mood.into(),
+                    old: Some(ParamValue::Mood(HcMood::Indicative)),
+                    new: Some(ParamValue::Mood(HcMood::Participle)),
+                },
+                ParamChange {
+                    param: FormParam::Declension,
+                    old: None,
+                    new: Some(ParamValue::Declension(
+                        HcGender::Masculine,
+                        HcCase::Nominative
+                    )),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_random_param_change_distribution() {
         let mut persons = [0, 0, 0];
@@ -741,9 +2530,15 @@ mod tests {
             ],
         };
 
-        let count = 100_000;
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let count = 10_000;
         for _i in 0..count {
-            a.change_params(num_changes, &parameters, &mut []);
+            a.change_params_with_options(
+                num_changes,
+                &parameters,
+                &RandomFormOptions::default(),
+                &mut rng,
+            );
             persons[a.person.unwrap().to_i16() as usize] += 1;
             numbers[a.number.unwrap().to_i16() as usize] += 1;
             tenses[a.tense.to_i16() as usize] += 1;
@@ -752,63 +2547,14 @@ mod tests {
 
             param_hash += a.param_hash();
         }
-        //sum of hash divided by count should be half of total number of possible forms (432 = 216)
-        assert!(
-            (param_hash as f64 / count as f64) > 214.0
-                && (param_hash as f64 / count as f64) < 218.0
-        );
-
-        //check distribution of each param:
-        assert!(
-            (persons[0] as f64 / count as f64) > 0.31 && (persons[0] as f64 / count as f64) < 0.35
-        );
-        assert!(
-            (persons[1] as f64 / count as f64) > 0.31 && (persons[1] as f64 / count as f64) < 0.35
-        );
-        assert!(
-            (persons[2] as f64 / count as f64) > 0.31 && (persons[2] as f64 / count as f64) < 0.35
-        );
-
-        assert!(
-            (numbers[0] as f64 / count as f64) > 0.48 && (numbers[0] as f64 / count as f64) < 0.52
-        );
-        assert!(
-            (numbers[1] as f64 / count as f64) > 0.48 && (numbers[1] as f64 / count as f64) < 0.52
-        );
-
-        assert!(
-            (tenses[0] as f64 / count as f64) > 0.14 && (tenses[0] as f64 / count as f64) < 0.18
-        );
-        assert!(
-            (tenses[1] as f64 / count as f64) > 0.14 && (tenses[1] as f64 / count as f64) < 0.18
-        );
-        assert!(
-            (tenses[2] as f64 / count as f64) > 0.14 && (tenses[2] as f64 / count as f64) < 0.18
-        );
-        assert!(
-            (tenses[3] as f64 / count as f64) > 0.14 && (tenses[3] as f64 / count as f64) < 0.18
-        );
-        assert!(
-            (tenses[4] as f64 / count as f64) > 0.14 && (tenses[4] as f64 / count as f64) < 0.18
-        );
-        assert!(
-            (tenses[5] as f64 / count as f64) > 0.14 && (tenses[5] as f64 / count as f64) < 0.18
-        );
-
-        assert!((moods[0] as f64 / count as f64) > 0.23 && (moods[0] as f64 / count as f64) < 0.27);
-        assert!((moods[1] as f64 / count as f64) > 0.23 && (moods[1] as f64 / count as f64) < 0.27);
-        assert!((moods[2] as f64 / count as f64) > 0.23 && (moods[2] as f64 / count as f64) < 0.27);
-        assert!((moods[3] as f64 / count as f64) > 0.23 && (moods[3] as f64 / count as f64) < 0.27);
-
-        assert!(
-            (voices[0] as f64 / count as f64) > 0.31 && (voices[0] as f64 / count as f64) < 0.35
-        );
-        assert!(
-            (voices[1] as f64 / count as f64) > 0.31 && (voices[1] as f64 / count as f64) < 0.35
-        );
-        assert!(
-            (voices[2] as f64 / count as f64) > 0.31 && (voices[2] as f64 / count as f64) < 0.35
-        );
+        // pinned to SEED, so any change to how the rng is drawn from shows up here;
+        // each param is still spread about evenly over its values
+        assert_eq!(param_hash, 2_159_785);
+        assert_eq!(persons, [3379, 3263, 3358]);
+        assert_eq!(numbers, [4990, 5010]);
+        assert_eq!(tenses, [1724, 1601, 1657, 1681, 1813, 1524]);
+        assert_eq!(moods, [2400, 2590, 2455, 2555]);
+        assert_eq!(voices, [3411, 3179, 3410]);
     }
 
     //same as above, but with oida = same results
@@ -855,9 +2601,15 @@ mod tests {
             ],
         };
 
+        let mut rng = StdRng::seed_from_u64(SEED);
         let count = 100_000;
         for _i in 0..count {
-            a.change_params(num_changes, &parameters, &mut []);
+            a.change_params_with_options(
+                num_changes,
+                &parameters,
+                &RandomFormOptions::default(),
+                &mut rng,
+            );
             persons[a.person.unwrap().to_i16() as usize] += 1;
             numbers[a.number.unwrap().to_i16() as usize] += 1;
             tenses[a.tense.to_i16() as usize] += 1;
@@ -0,0 +1,285 @@
+use crate::answer::{alternatives, check_answer};
+use crate::names::ParamName;
+use crate::{try_extract_params_from_hash, GetRandom};
+use hoplite_verbs_rs::*;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Number of finite cells, i.e. of `param_hash` values with a person and number.
+pub const NUM_CELLS: u32 = 432;
+
+// the last step of get_form, or None for an error or "—"
+pub fn form_string(vf: &HcGreekVerbForm) -> Option<String> {
+    vf.get_form(false)
+        .ok()
+        .and_then(|steps| steps.last().map(|s| s.form.clone()))
+        .filter(|f| f != "—")
+}
+
+/// Every finite cell of the verb, in `param_hash` order; some may have no form.
+pub fn all_forms(verb: &Arc<HcGreekVerb>) -> Vec<HcGreekVerbForm> {
+    (0..NUM_CELLS)
+        .map(|hash| {
+            let mut vf = HcGreekVerbForm {
+                verb: verb.clone(),
+                person: None,
+                number: None,
+                tense: HcTense::Present,
+                voice: HcVoice::Active,
+                mood: HcMood::Indicative,
+                gender: None,
+                case: None,
+            };
+            try_extract_params_from_hash(&mut vf, hash).unwrap();
+            vf
+        })
+        .collect()
+}
+
+/// Every cell of the verb whose form the answer matches, as accepted by `check_answer`.
+pub fn lookup_answer(answer: &str, verb: &Arc<HcGreekVerb>) -> Vec<HcGreekVerbForm> {
+    all_forms(verb)
+        .into_iter()
+        .filter(|vf| form_string(vf).is_some_and(|f| check_answer(answer, &f).is_ok()))
+        .collect()
+}
+
+pub fn lookup_answer_hashes(answer: &str, verb: &Arc<HcGreekVerb>) -> Vec<u32> {
+    lookup_answer(answer, verb)
+        .iter()
+        .map(|vf| vf.param_hash())
+        .collect()
+}
+
+// cells whose forms share a spelling, directly or through another cell, each group
+// in param_hash order
+fn group_by_form(forms: Vec<(HcGreekVerbForm, String)>) -> Vec<Vec<HcGreekVerbForm>> {
+    let mut groups: Vec<(Vec<HcGreekVerbForm>, HashSet<String>)> = vec![];
+    for (vf, form) in forms {
+        let alts: HashSet<String> = alternatives(&form).into_iter().collect();
+        let (overlapping, rest): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .partition(|(_, group_alts)| !group_alts.is_disjoint(&alts));
+        let mut group = (vec![vf], alts);
+        for (cells, group_alts) in overlapping {
+            group.0.extend(cells);
+            group.1.extend(group_alts);
+        }
+        groups = rest;
+        groups.push(group);
+    }
+    let mut groups: Vec<Vec<HcGreekVerbForm>> = groups
+        .into_iter()
+        .map(|(mut cells, _)| {
+            cells.sort_by_key(|vf| vf.param_hash());
+            cells
+        })
+        .filter(|cells| cells.len() > 1)
+        .collect();
+    groups.sort_by_key(|cells| cells[0].param_hash());
+    groups
+}
+
+/// Groups of two or more finite cells of the verb whose forms share a spelling, e.g. λύσω
+/// as future indicative and aorist subjunctive, or λύσει, λύσῃ with λύσει and with λύσῃ.
+/// Cells without a form are left out.
+pub fn homographs(verb: &Arc<HcGreekVerb>) -> Vec<Vec<HcGreekVerbForm>> {
+    group_by_form(
+        all_forms(verb)
+            .into_iter()
+            .filter_map(|vf| form_string(&vf).map(|f| (vf, f)))
+            .collect(),
+    )
+}
+
+// every cell of the verb in a homograph group
+// pass as filter_forms to drill only unambiguous cells, with RetryBudget::strict(),
+// since the default budget stops honouring the filter after a while
+pub fn ambiguous_hashes(verb: &Arc<HcGreekVerb>) -> HashSet<u32> {
+    homographs(verb)
+        .iter()
+        .flatten()
+        .map(|vf| vf.param_hash())
+        .collect()
+}
+
+// every cell of the verb in no homograph group
+// pass as filter_forms, with RetryBudget::strict(), to drill only the confusable cells
+pub fn unambiguous_hashes(verb: &Arc<HcGreekVerb>) -> HashSet<u32> {
+    let ambiguous = ambiguous_hashes(verb);
+    (0..NUM_CELLS).filter(|h| !ambiguous.contains(h)).collect()
+}
+
+// the values of vf that differ from other, e.g. "aorist subjunctive"
+fn describe_differences(vf: &HcGreekVerbForm, other: &HcGreekVerbForm) -> String {
+    let mut words = vec![];
+    if vf.person != other.person {
+        words.extend(vf.person.map(|p| p.name()));
+    }
+    if vf.number != other.number {
+        words.extend(vf.number.map(|n| n.name()));
+    }
+    if vf.tense != other.tense {
+        words.push(vf.tense.name());
+    }
+    if vf.voice != other.voice {
+        words.push(vf.voice.name());
+    }
+    if vf.mood != other.mood {
+        words.push(vf.mood.name());
+    }
+    words.join(" ")
+}
+
+fn num_differences(vf: &HcGreekVerbForm, other: &HcGreekVerbForm) -> usize {
+    [
+        vf.person != other.person,
+        vf.number != other.number,
+        vf.tense != other.tense,
+        vf.voice != other.voice,
+        vf.mood != other.mood,
+    ]
+    .iter()
+    .filter(|d| **d)
+    .count()
+}
+
+/// Feedback for a wrong answer that is a real form of the asked verb in another cell, e.g.
+/// "that's the aorist subjunctive; you were asked for the future indicative".
+/// None if the answer is right or is not a form of the verb; the closest cell is reported.
+pub fn describe_confusion(asked: &HcGreekVerbForm, answer: &str) -> Option<String> {
+    let matches = lookup_answer(answer, &asked.verb);
+    if matches
+        .iter()
+        .any(|vf| vf.param_hash() == asked.param_hash())
+    {
+        return None;
+    }
+    let closest = matches.iter().min_by_key(|vf| num_differences(vf, asked))?;
+    Some(format!(
+        "that's the {}; you were asked for the {}",
+        describe_differences(closest, asked),
+        describe_differences(asked, closest)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answer::same_form;
+
+    #[test]
+    fn test_lookup_answer() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let forms = all_forms(&verb);
+        assert_eq!(forms.len(), NUM_CELLS as usize);
+        for (hash, vf) in forms.iter().enumerate() {
+            assert_eq!(vf.param_hash(), hash as u32);
+        }
+
+        let asked = HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Future,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let typed = HcGreekVerbForm {
+            tense: HcTense::Aorist,
+            mood: HcMood::Subjunctive,
+            ..asked.clone()
+        };
+        let answer = form_string(&typed).unwrap();
+        assert!(lookup_answer_hashes(&answer, &verb).contains(&typed.param_hash()));
+        assert_eq!(
+            describe_confusion(&asked, &answer),
+            Some(
+                "that's the aorist subjunctive; you were asked for the future indicative"
+                    .to_string()
+            )
+        );
+
+        assert_eq!(
+            describe_confusion(&asked, &form_string(&asked).unwrap()),
+            None
+        );
+        assert!(lookup_answer("οὐδέν", &verb).is_empty());
+        assert_eq!(describe_confusion(&asked, "οὐδέν"), None);
+    }
+
+    #[test]
+    fn test_homographs() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let forms = all_forms(&verb);
+
+        let groups = group_by_form(vec![
+            (forms[0].clone(), "λύω".to_string()),
+            (forms[1].clone(), "λύεις".to_string()),
+            (forms[2].clone(), "λυ\u{0301}ω".to_string()),
+            (forms[3].clone(), "λύεις".to_string()),
+            (forms[4].clone(), "λύει".to_string()),
+            // sharing one of two spellings is enough, in either order
+            (forms[5].clone(), "λύσῃ".to_string()),
+            (forms[6].clone(), "λύσει, λύσῃ".to_string()),
+            (forms[7].clone(), "λύσει".to_string()),
+        ]);
+        assert_eq!(
+            groups,
+            vec![
+                vec![forms[0].clone(), forms[2].clone()],
+                vec![forms[1].clone(), forms[3].clone()],
+                vec![forms[5].clone(), forms[6].clone(), forms[7].clone()],
+            ]
+        );
+
+        for group in homographs(&verb) {
+            assert!(group.len() > 1);
+            assert!(group.iter().all(|vf| group.iter().any(|other| other != vf
+                && same_form(&form_string(vf).unwrap(), &form_string(other).unwrap()))));
+        }
+        let ambiguous = ambiguous_hashes(&verb);
+        let unambiguous = unambiguous_hashes(&verb);
+        assert!(ambiguous.is_disjoint(&unambiguous));
+        assert_eq!(ambiguous.len() + unambiguous.len(), NUM_CELLS as usize);
+
+        // λύσει, λύσῃ as future middle with λύσει and λύσῃ in other cells
+        let luw = "λύω, λύσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let cell = |person, tense, voice, mood| HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(person),
+            number: Some(HcNumber::Singular),
+            tense,
+            voice,
+            mood,
+            gender: None,
+            case: None,
+        };
+        let middle = cell(
+            HcPerson::Second,
+            HcTense::Future,
+            HcVoice::Middle,
+            HcMood::Indicative,
+        );
+        let groups = homographs(&verb);
+        let group = groups.iter().find(|g| g.contains(&middle)).unwrap();
+        assert!(group.contains(&cell(
+            HcPerson::Third,
+            HcTense::Future,
+            HcVoice::Active,
+            HcMood::Indicative
+        )));
+        assert!(group.contains(&cell(
+            HcPerson::Third,
+            HcTense::Aorist,
+            HcVoice::Active,
+            HcMood::Subjunctive
+        )));
+        assert!(ambiguous_hashes(&verb).contains(&middle.param_hash()));
+    }
+}
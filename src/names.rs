@@ -0,0 +1,188 @@
+use hoplite_verbs_rs::*;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+/// Lower-case names for parameter values, as used in curriculum files and messages.
+pub trait ParamName: Sized {
+    fn name(&self) -> &'static str;
+    fn from_name(name: &str) -> Option<Self>;
+}
+
+macro_rules! param_names {
+    ($t:ty { $($variant:path => $name:literal),* $(,)? }) => {
+        impl ParamName for $t {
+            fn name(&self) -> &'static str {
+                match self {
+                    $($variant => $name,)*
+                }
+            }
+
+            fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some($variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+param_names!(HcPerson {
+    HcPerson::First => "first",
+    HcPerson::Second => "second",
+    HcPerson::Third => "third",
+});
+
+param_names!(HcNumber {
+    HcNumber::Singular => "singular",
+    HcNumber::Plural => "plural",
+});
+
+param_names!(HcTense {
+    HcTense::Present => "present",
+    HcTense::Imperfect => "imperfect",
+    HcTense::Future => "future",
+    HcTense::Aorist => "aorist",
+    HcTense::Perfect => "perfect",
+    HcTense::Pluperfect => "pluperfect",
+});
+
+param_names!(HcMood {
+    HcMood::Indicative => "indicative",
+    HcMood::Subjunctive => "subjunctive",
+    HcMood::Optative => "optative",
+    HcMood::Imperative => "imperative",
+    HcMood::Infinitive => "infinitive",
+    HcMood::Participle => "participle",
+});
+
+param_names!(HcVoice {
+    HcVoice::Active => "active",
+    HcVoice::Middle => "middle",
+    HcVoice::Passive => "passive",
+});
+
+param_names!(HcGender {
+    HcGender::Masculine => "masculine",
+    HcGender::Feminine => "feminine",
+    HcGender::Neuter => "neuter",
+});
+
+param_names!(HcCase {
+    HcCase::Nominative => "nominative",
+    HcCase::Genitive => "genitive",
+    HcCase::Dative => "dative",
+    HcCase::Accusative => "accusative",
+    HcCase::Vocative => "vocative",
+});
+
+// e.g. "first singular present active indicative", or for a participle
+// "plural aorist passive participle neuter genitive"; params that are unset are left out
+pub fn describe_form(vf: &HcGreekVerbForm) -> String {
+    let mut words = vec![];
+    if let Some(person) = vf.person {
+        words.push(person.name());
+    }
+    if let Some(number) = vf.number {
+        words.push(number.name());
+    }
+    words.extend([vf.tense.name(), vf.voice.name(), vf.mood.name()]);
+    if let Some(gender) = vf.gender {
+        words.push(gender.name());
+    }
+    if let Some(case) = vf.case {
+        words.push(case.name());
+    }
+    words.join(" ")
+}
+
+// the params that differ between from and to, e.g. "person: first → second"
+pub fn describe_changes(from: &HcGreekVerbForm, to: &HcGreekVerbForm) -> Vec<String> {
+    let mut changes = vec![];
+    if from.person != to.person {
+        changes.push(format!(
+            "person: {} → {}",
+            from.person.map_or("none", |p| p.name()),
+            to.person.map_or("none", |p| p.name())
+        ));
+    }
+    if from.number != to.number {
+        changes.push(format!(
+            "number: {} → {}",
+            from.number.map_or("none", |n| n.name()),
+            to.number.map_or("none", |n| n.name())
+        ));
+    }
+    if from.tense != to.tense {
+        changes.push(format!(
+            "tense: {} → {}",
+            from.tense.name(),
+            to.tense.name()
+        ));
+    }
+    if from.voice != to.voice {
+        changes.push(format!(
+            "voice: {} → {}",
+            from.voice.name(),
+            to.voice.name()
+        ));
+    }
+    if from.mood != to.mood {
+        changes.push(format!("mood: {} → {}", from.mood.name(), to.mood.name()));
+    }
+    if from.gender != to.gender {
+        changes.push(format!(
+            "gender: {} → {}",
+            from.gender.map_or("none", |g| g.name()),
+            to.gender.map_or("none", |g| g.name())
+        ));
+    }
+    if from.case != to.case {
+        changes.push(format!(
+            "case: {} → {}",
+            from.case.map_or("none", |c| c.name()),
+            to.case.map_or("none", |c| c.name())
+        ));
+    }
+    changes
+}
+
+// for optional lists of names, e.g. "voices": ["active", "passive"]
+pub(crate) fn deserialize_names<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ParamName,
+{
+    let names: Option<Vec<String>> = Option::deserialize(deserializer)?;
+    names
+        .map(|names| {
+            names
+                .iter()
+                .map(|n| {
+                    T::from_name(n).ok_or_else(|| D::Error::custom(format!("unknown value: {}", n)))
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_round_trip() {
+        for t in [
+            HcTense::Present,
+            HcTense::Imperfect,
+            HcTense::Future,
+            HcTense::Aorist,
+            HcTense::Perfect,
+            HcTense::Pluperfect,
+        ] {
+            assert_eq!(HcTense::from_name(t.name()), Some(t));
+        }
+        assert_eq!(HcVoice::from_name("middle"), Some(HcVoice::Middle));
+        assert_eq!(HcMood::from_name("Middle"), None);
+    }
+}
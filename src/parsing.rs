@@ -0,0 +1,288 @@
+use crate::answer::same_form;
+use crate::lookup::{all_forms, form_string};
+use crate::names::ParamName;
+use crate::{is_finite, ChooserError, GetRandom, RandomFormOptions};
+use hoplite_verbs_rs::*;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// A student's parse of a form, e.g. from "second plural aorist middle subjunctive".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parse {
+    pub person: Option<HcPerson>,
+    pub number: Option<HcNumber>,
+    pub tense: HcTense,
+    pub voice: HcVoice,
+    pub mood: HcMood,
+}
+
+impl Parse {
+    /// Reads the five values as names in any order.
+    pub fn from_words(text: &str) -> Result<Parse, String> {
+        let (mut person, mut number, mut tense, mut voice, mut mood) =
+            (None, None, None, None, None);
+        for word in text.split_whitespace().map(|w| w.to_lowercase()) {
+            if let Some(p) = HcPerson::from_name(&word) {
+                person = Some(p);
+            } else if let Some(n) = HcNumber::from_name(&word) {
+                number = Some(n);
+            } else if let Some(t) = HcTense::from_name(&word) {
+                tense = Some(t);
+            } else if let Some(v) = HcVoice::from_name(&word) {
+                voice = Some(v);
+            } else if let Some(m) = HcMood::from_name(&word) {
+                mood = Some(m);
+            } else {
+                return Err(format!("unknown value: {}", word));
+            }
+        }
+        Ok(Parse {
+            person,
+            number,
+            tense: tense.ok_or("missing tense")?,
+            voice: voice.ok_or("missing voice")?,
+            mood: mood.ok_or("missing mood")?,
+        })
+    }
+
+    pub fn matches(&self, vf: &HcGreekVerbForm) -> bool {
+        self.person == vf.person
+            && self.number == vf.number
+            && self.tense == vf.tense
+            && self.voice == vf.voice
+            && self.mood == vf.mood
+    }
+}
+
+/// A form to parse. Any cell of the verb sharing a spelling with the form is accepted, so
+/// for λύσω both the future indicative and the aorist subjunctive are right.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsingQuestion {
+    pub form: HcGreekVerbForm,
+    pub greek: String,
+    pub accepted: Vec<HcGreekVerbForm>,
+}
+
+impl ParsingQuestion {
+    pub fn new(form: HcGreekVerbForm) -> Option<ParsingQuestion> {
+        let greek = form_string(&form)?;
+        let accepted = all_forms(&form.verb)
+            .into_iter()
+            .filter(|vf| form_string(vf).is_some_and(|f| same_form(&f, &greek)))
+            .collect();
+        Some(ParsingQuestion {
+            form,
+            greek,
+            accepted,
+        })
+    }
+
+    pub fn check(&self, parse: &Parse) -> bool {
+        parse.matches(&self.form) || self.accepted.iter().any(|vf| parse.matches(vf))
+    }
+}
+
+/// Chooses the next form to parse from the previous one with `random_form_with_options`.
+/// Only finite moods are asked, since a `Parse` has no gender or case.
+pub fn next_parsing_question<R: Rng + ?Sized>(
+    prev: &HcGreekVerbForm,
+    num_changes: u8,
+    highest_unit: Option<i16>,
+    parameters: &VerbParameters,
+    filter_forms: Option<&HashSet<u32>>,
+    options: &RandomFormOptions,
+    rng: &mut R,
+) -> Result<ParsingQuestion, ChooserError> {
+    let parameters = VerbParameters {
+        persons: parameters.persons.clone(),
+        numbers: parameters.numbers.clone(),
+        tenses: parameters.tenses.clone(),
+        voices: parameters.voices.clone(),
+        moods: parameters
+            .moods
+            .iter()
+            .copied()
+            .filter(|m| is_finite(*m))
+            .collect(),
+    };
+    if parameters.moods.is_empty() {
+        return Err(ChooserError::NoFormsForParameters);
+    }
+    let (form, _, _) = prev.random_form_with_options(
+        num_changes,
+        highest_unit,
+        &parameters,
+        filter_forms,
+        options,
+        rng,
+    )?;
+    // random_form_with_options only returns forms that get_form can make
+    ParsingQuestion::new(form).ok_or(ChooserError::NoFormsForParameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parsing_question() {
+        assert_eq!(
+            Parse::from_words("Aorist second plural middle subjunctive"),
+            Ok(Parse {
+                person: Some(HcPerson::Second),
+                number: Some(HcNumber::Plural),
+                tense: HcTense::Aorist,
+                voice: HcVoice::Middle,
+                mood: HcMood::Subjunctive,
+            })
+        );
+        assert!(Parse::from_words("second plural aorist middle").is_err());
+        assert!(Parse::from_words("second plural aorist middle subjunctive dual").is_err());
+
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let prev = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let parameters = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present, HcTense::Future, HcTense::Aorist],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Indicative, HcMood::Subjunctive],
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        let q = next_parsing_question(
+            &prev,
+            2,
+            None,
+            &parameters,
+            None,
+            &RandomFormOptions::default(),
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(Some(q.greek.clone()), form_string(&q.form));
+        assert!(q.accepted.contains(&q.form));
+        let right = Parse {
+            person: q.form.person,
+            number: q.form.number,
+            tense: q.form.tense,
+            voice: q.form.voice,
+            mood: q.form.mood,
+        };
+        assert!(q.check(&right));
+        assert!(!q.check(&Parse {
+            voice: HcVoice::Passive,
+            ..right
+        }));
+
+        // every cell with the same form is accepted
+        let homograph = HcGreekVerbForm {
+            tense: HcTense::Aorist,
+            mood: HcMood::Subjunctive,
+            ..prev.clone()
+        };
+        let q = ParsingQuestion {
+            form: prev.clone(),
+            greek: form_string(&prev).unwrap(),
+            accepted: vec![prev.clone(), homograph],
+        };
+        assert!(q.check(&Parse {
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Aorist,
+            voice: HcVoice::Active,
+            mood: HcMood::Subjunctive,
+        }));
+
+        // infinitives and participles are never asked
+        let parameters = VerbParameters {
+            moods: vec![HcMood::Indicative, HcMood::Infinitive, HcMood::Participle],
+            ..parameters
+        };
+        for _ in 0..50 {
+            let q = next_parsing_question(
+                &prev,
+                2,
+                None,
+                &parameters,
+                None,
+                &RandomFormOptions::default(),
+                &mut rng,
+            )
+            .unwrap();
+            assert_eq!(q.form.mood, HcMood::Indicative);
+        }
+        let parameters = VerbParameters {
+            moods: vec![HcMood::Infinitive],
+            ..parameters
+        };
+        assert_eq!(
+            next_parsing_question(
+                &prev,
+                2,
+                None,
+                &parameters,
+                None,
+                &RandomFormOptions::default(),
+                &mut rng,
+            ),
+            Err(ChooserError::NoFormsForParameters)
+        );
+    }
+
+    #[test]
+    fn test_accepted_homographs() {
+        use HcMood::*;
+        use HcPerson::*;
+        use HcTense::*;
+        use HcVoice::*;
+
+        let luw = "λύω, λύσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let cell = |person, tense, voice, mood| HcGreekVerbForm {
+            verb: verb.clone(),
+            person: Some(person),
+            number: Some(HcNumber::Singular),
+            tense,
+            voice,
+            mood,
+            gender: None,
+            case: None,
+        };
+        let accepted = |vf: HcGreekVerbForm| ParsingQuestion::new(vf).unwrap().accepted;
+
+        // λύσω
+        let future = cell(First, Future, Active, Indicative);
+        let subjunctive = cell(First, Aorist, Active, Subjunctive);
+        assert_eq!(accepted(future.clone()), vec![future, subjunctive]);
+
+        // ἔλυσε(ν)
+        let aorist = cell(Third, Aorist, Active, Indicative);
+        assert_eq!(accepted(aorist.clone()), vec![aorist]);
+
+        // λύσει, λύσῃ
+        let middle = cell(Second, Future, Middle, Indicative);
+        let q = ParsingQuestion::new(middle.clone()).unwrap();
+        assert!(q.greek.contains(','));
+        for vf in [
+            middle,
+            cell(Third, Future, Active, Indicative),
+            cell(Third, Aorist, Active, Subjunctive),
+            cell(Second, Aorist, Middle, Subjunctive),
+        ] {
+            assert!(q.accepted.contains(&vf), "{:?}", vf);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{reachable_forms, GetRandom, RandomFormOptions};
+use hoplite_verbs_rs::*;
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// SM-2 review state for one verb × cell pair. Times are seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReviewState {
+    pub repetitions: u32,
+    pub interval_days: f64,
+    pub ease: f64,
+    pub due: u64,
+}
+
+impl Default for ReviewState {
+    fn default() -> Self {
+        ReviewState {
+            repetitions: 0,
+            interval_days: 0.0,
+            ease: 2.5,
+            due: 0,
+        }
+    }
+}
+
+impl ReviewState {
+    /// quality is the SM-2 grade: 0 (blackout) to 5 (perfect); below 3 starts the cell over
+    pub fn review(&mut self, quality: u8, now: u64) {
+        let q = f64::from(quality.min(5));
+        if quality >= 3 {
+            self.interval_days = match self.repetitions {
+                0 => 1.0,
+                1 => 6.0,
+                _ => self.interval_days * self.ease,
+            };
+            self.repetitions += 1;
+        } else {
+            self.repetitions = 0;
+            self.interval_days = 1.0;
+        }
+        self.ease = (self.ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(1.3);
+        self.due = now + (self.interval_days * SECONDS_PER_DAY) as u64;
+    }
+
+    pub fn is_due(&self, now: u64) -> bool {
+        self.due <= now
+    }
+}
+
+/// Keyed by verb id and `param_hash`.
+pub type CellKey = (u32, u32);
+
+pub fn cell_key(vf: &HcGreekVerbForm) -> CellKey {
+    (vf.verb.id, vf.param_hash())
+}
+
+/// Chooses the next form for one learner, preferring cells that are due for review.
+///
+/// Candidates are the forms `reachable_forms` gives from the current form, so unit gating
+/// and the N-change rule still apply. Among them the scheduler picks the most overdue cell,
+/// then a cell never seen before, then whichever cell comes due soonest.
+pub struct Scheduler {
+    pub num_changes: u8,
+    pub highest_unit: Option<i16>,
+    pub parameters: VerbParameters,
+    pub options: RandomFormOptions,
+    /// Review state per cell, for saving and restoring a learner's progress.
+    pub states: HashMap<CellKey, ReviewState>,
+    current: HcGreekVerbForm,
+}
+
+impl Scheduler {
+    pub fn new(
+        start: HcGreekVerbForm,
+        num_changes: u8,
+        highest_unit: Option<i16>,
+        parameters: VerbParameters,
+    ) -> Scheduler {
+        Scheduler {
+            num_changes,
+            highest_unit,
+            parameters,
+            options: RandomFormOptions::default(),
+            states: HashMap::new(),
+            current: start,
+        }
+    }
+
+    pub fn current(&self) -> &HcGreekVerbForm {
+        &self.current
+    }
+
+    // e.g. to move on to a new verb; review states are kept
+    pub fn set_current(&mut self, vf: HcGreekVerbForm) {
+        self.current = vf;
+    }
+
+    pub fn state(&self, vf: &HcGreekVerbForm) -> Option<&ReviewState> {
+        self.states.get(&cell_key(vf))
+    }
+
+    pub fn record(&mut self, vf: &HcGreekVerbForm, quality: u8, now: u64) {
+        self.states
+            .entry(cell_key(vf))
+            .or_default()
+            .review(quality, now);
+    }
+
+    // a right answer counts as SM-2 quality 4, a wrong one as 1
+    pub fn record_answer(&mut self, vf: &HcGreekVerbForm, correct: bool, now: u64) {
+        self.record(vf, if correct { 4 } else { 1 }, now);
+    }
+
+    pub fn next_form(&mut self, now: u64) -> Option<HcGreekVerbForm> {
+        self.next_form_with_rng(now, &mut rand::thread_rng())
+    }
+
+    pub fn next_form_with_rng<R: Rng + ?Sized>(
+        &mut self,
+        now: u64,
+        rng: &mut R,
+    ) -> Option<HcGreekVerbForm> {
+        let candidates = reachable_forms(
+            &self.current,
+            self.num_changes,
+            self.highest_unit,
+            &self.parameters,
+            None,
+            &self.options,
+        );
+
+        let most_overdue = candidates
+            .iter()
+            .filter_map(|c| self.state(c).map(|s| s.due))
+            .filter(|due| *due <= now)
+            .min();
+        let unseen: Vec<&HcGreekVerbForm> = candidates
+            .iter()
+            .filter(|c| self.state(c).is_none())
+            .collect();
+
+        let chosen = if let Some(due) = most_overdue {
+            candidates
+                .iter()
+                .filter(|c| self.state(c).is_some_and(|s| s.due == due))
+                .collect::<Vec<_>>()
+                .choose(rng)
+                .copied()
+        } else if !unseen.is_empty() {
+            unseen.choose(rng).copied()
+        } else {
+            candidates
+                .iter()
+                .min_by_key(|c| self.state(c).map_or(u64::MAX, |s| s.due))
+        };
+
+        let chosen = chosen.cloned()?;
+        self.current = chosen.clone();
+        Some(chosen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Arc;
+
+    const DAY: u64 = 86_400;
+
+    #[test]
+    fn test_sm2_intervals() {
+        let mut s = ReviewState::default();
+        s.review(4, 0);
+        assert_eq!(s.due, DAY);
+        s.review(4, DAY);
+        assert_eq!(s.due, 7 * DAY);
+        s.review(4, 7 * DAY);
+        assert_eq!(s.repetitions, 3);
+        assert!(s.interval_days > 14.0);
+
+        // a miss starts over
+        s.review(1, 30 * DAY);
+        assert_eq!(s.repetitions, 0);
+        assert_eq!(s.due, 31 * DAY);
+        assert!(s.ease < 2.5 && s.ease >= 1.3);
+    }
+
+    #[test]
+    fn test_next_form_prefers_due_cells() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let a = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let verb_params = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular],
+            tenses: vec![HcTense::Present],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Indicative],
+        };
+        let second = HcGreekVerbForm {
+            person: Some(HcPerson::Second),
+            ..a.clone()
+        };
+        let third = HcGreekVerbForm {
+            person: Some(HcPerson::Third),
+            ..a.clone()
+        };
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut scheduler = Scheduler::new(a.clone(), 1, Some(2), verb_params);
+
+        // unseen cells come before cells that aren't due yet
+        scheduler.record_answer(&second, true, 0);
+        assert_eq!(
+            scheduler.next_form_with_rng(0, &mut rng),
+            Some(third.clone())
+        );
+
+        // the missed cell comes due first
+        scheduler.record_answer(&second, true, DAY);
+        scheduler.record_answer(&third, false, DAY);
+        scheduler.set_current(a);
+        assert_eq!(
+            scheduler.next_form_with_rng(2 * DAY, &mut rng),
+            Some(third.clone())
+        );
+
+        // nothing due: the one that comes due soonest
+        scheduler.record_answer(&third, true, 2 * DAY);
+        scheduler.set_current(HcGreekVerbForm {
+            person: Some(HcPerson::First),
+            ..third.clone()
+        });
+        assert_eq!(scheduler.next_form_with_rng(2 * DAY, &mut rng), Some(third));
+    }
+}
@@ -0,0 +1,307 @@
+use crate::answer::{check_answer, Mistake};
+use crate::clock::{Clock, SystemClock};
+use crate::lookup::form_string;
+use crate::{changed_params, ChooserError, GetRandom, RandomFormOptions};
+use hoplite_verbs_rs::*;
+use rand::Rng;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+/// One question: change `prev` into `form`, whose right answer is `answer`.
+/// Times are from the session's `Clock`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prompt {
+    pub prev: HcGreekVerbForm,
+    pub form: HcGreekVerbForm,
+    pub answer: String,
+    pub asked_at: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub prompt: Prompt,
+    pub answer: String,
+    pub result: Result<(), Mistake>,
+    pub answered_at: Duration,
+}
+
+/// `Diagnostics` summed over every form chosen in a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiagnosticTotals {
+    pub dash: u32,
+    pub middle_passive: u32,
+    pub blocked_for_unit: u32,
+    pub filtered: u32,
+    pub illegal: u32,
+}
+
+impl DiagnosticTotals {
+    pub fn add(&mut self, diag: &Diagnostics) {
+        self.dash += diag.dash;
+        self.middle_passive += diag.middle_passive;
+        self.blocked_for_unit += diag.blocked_for_unit;
+        self.filtered += diag.filtered;
+        self.illegal += diag.illegal;
+    }
+}
+
+/// A drill for one student: chooses each prompt from the last form asked, keeps the
+/// recently used cells of each verb out of the next prompts, and keeps score.
+pub struct DrillSession<C: Clock = SystemClock> {
+    pub num_changes: u8,
+    pub highest_unit: Option<i16>,
+    pub parameters: VerbParameters,
+    pub options: RandomFormOptions,
+    /// How many of the most recent cells of each verb are filtered out. The filter is best
+    /// effort: the default `options.budget` drops it rather than fail when it rules out too
+    /// much; use `RetryBudget::strict()` to always keep it.
+    pub filter_len: usize,
+    clock: C,
+    current: HcGreekVerbForm,
+    pending: Option<Prompt>,
+    used: HashMap<u32, VecDeque<u32>>,
+    history: Vec<Response>,
+    score: u32,
+    streak: u32,
+    best_streak: u32,
+    totals: DiagnosticTotals,
+}
+
+impl DrillSession {
+    pub fn new(
+        start: HcGreekVerbForm,
+        num_changes: u8,
+        highest_unit: Option<i16>,
+        parameters: VerbParameters,
+    ) -> DrillSession {
+        DrillSession::with_clock(
+            start,
+            num_changes,
+            highest_unit,
+            parameters,
+            SystemClock::default(),
+        )
+    }
+}
+
+impl<C: Clock> DrillSession<C> {
+    pub fn with_clock(
+        start: HcGreekVerbForm,
+        num_changes: u8,
+        highest_unit: Option<i16>,
+        parameters: VerbParameters,
+        clock: C,
+    ) -> DrillSession<C> {
+        DrillSession {
+            num_changes,
+            highest_unit,
+            parameters,
+            options: RandomFormOptions::default(),
+            filter_len: 20,
+            clock,
+            current: start,
+            pending: None,
+            used: HashMap::new(),
+            history: vec![],
+            score: 0,
+            streak: 0,
+            best_streak: 0,
+            totals: DiagnosticTotals::default(),
+        }
+    }
+
+    // the form the next prompt changes
+    pub fn current(&self) -> &HcGreekVerbForm {
+        &self.current
+    }
+
+    // e.g. to move on to a new verb; an unanswered prompt is dropped
+    pub fn set_current(&mut self, vf: HcGreekVerbForm) {
+        self.current = vf;
+        self.pending = None;
+    }
+
+    pub fn pending(&self) -> Option<&Prompt> {
+        self.pending.as_ref()
+    }
+
+    pub fn history(&self) -> &[Response] {
+        &self.history
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn streak(&self) -> u32 {
+        self.streak
+    }
+
+    pub fn best_streak(&self) -> u32 {
+        self.best_streak
+    }
+
+    pub fn diagnostics(&self) -> &DiagnosticTotals {
+        &self.totals
+    }
+
+    // the cells of the current verb kept out of the next prompt
+    pub fn filter(&self) -> HashSet<u32> {
+        self.used
+            .get(&self.current.verb.id)
+            .map(|used| used.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn next_prompt(&mut self) -> Result<&Prompt, ChooserError> {
+        self.next_prompt_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Chooses the next prompt, or gives the unanswered one again.
+    pub fn next_prompt_with_rng<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<&Prompt, ChooserError> {
+        if self.pending.is_none() {
+            let filter = self.filter();
+            // the change that made the current form, unless it was set by hand since
+            let last_change = self
+                .history
+                .last()
+                .filter(|r| r.prompt.form == self.current)
+                .map(|r| changed_params(&r.prompt.prev, &r.prompt.form))
+                .unwrap_or_default();
+            let options = RandomFormOptions {
+                last_change,
+                ..self.options.clone()
+            };
+            let (form, diag, _) = self.current.random_form_with_options(
+                self.num_changes,
+                self.highest_unit,
+                &self.parameters,
+                Some(&filter),
+                &options,
+                rng,
+            )?;
+            self.totals.add(&diag);
+            let answer = form_string(&form).ok_or(ChooserError::NoFormsForParameters)?;
+
+            let used = self.used.entry(form.verb.id).or_default();
+            used.push_back(form.param_hash());
+            while used.len() > self.filter_len {
+                used.pop_front();
+            }
+
+            self.pending = Some(Prompt {
+                prev: self.current.clone(),
+                form,
+                answer,
+                asked_at: self.clock.now(),
+            });
+        }
+        Ok(self.pending.as_ref().unwrap())
+    }
+
+    /// Grades the answer to the pending prompt, and the prompt's form becomes the one
+    /// the next prompt changes. None if there is no pending prompt.
+    pub fn submit(&mut self, answer: &str) -> Option<Result<(), Mistake>> {
+        let prompt = self.pending.take()?;
+        let result = check_answer(answer, &prompt.answer);
+        if result.is_ok() {
+            self.score += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.streak = 0;
+        }
+        self.current = prompt.form.clone();
+        self.history.push(Response {
+            prompt,
+            answer: answer.to_string(),
+            result,
+            answered_at: self.clock.now(),
+        });
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_drill_session() {
+        let luw = "λω, λσω, ἔλῡσα, λέλυκα, λέλυμαι, ἐλύθην";
+        let verb = Arc::new(HcGreekVerb::from_string(1, luw, REGULAR, 0).unwrap());
+        let start = HcGreekVerbForm {
+            verb,
+            person: Some(HcPerson::First),
+            number: Some(HcNumber::Singular),
+            tense: HcTense::Present,
+            voice: HcVoice::Active,
+            mood: HcMood::Indicative,
+            gender: None,
+            case: None,
+        };
+        let parameters = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![HcTense::Present, HcTense::Future, HcTense::Aorist],
+            voices: vec![HcVoice::Active],
+            moods: vec![HcMood::Indicative],
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+        let time = Rc::new(Cell::new(Duration::from_secs(5)));
+        let mut session =
+            DrillSession::with_clock(start.clone(), 1, None, parameters, TestClock(time.clone()));
+        session.filter_len = 4;
+        assert_eq!(session.submit("λύω"), None);
+
+        let first = session.next_prompt_with_rng(&mut rng).unwrap().clone();
+        assert_eq!(first.prev, start);
+        // asking again gives the same prompt until it is answered
+        assert_eq!(session.next_prompt_with_rng(&mut rng).unwrap(), &first);
+        assert_eq!(session.submit(&first.answer), Some(Ok(())));
+        assert_eq!(session.current(), &first.form);
+
+        let second = session.next_prompt_with_rng(&mut rng).unwrap().clone();
+        assert_eq!(second.prev, first.form);
+        assert_ne!(second.form.param_hash(), first.form.param_hash());
+        assert_eq!(session.submit(&second.answer), Some(Ok(())));
+        assert_eq!((session.score(), session.streak()), (2, 2));
+
+        session.next_prompt_with_rng(&mut rng).unwrap();
+        time.set(Duration::from_secs(12));
+        assert!(session.submit("οὐδέν").unwrap().is_err());
+        assert_eq!((session.score(), session.streak()), (2, 0));
+        assert_eq!(session.best_streak(), 2);
+        assert_eq!(session.history().len(), 3);
+        assert_eq!(session.history()[2].answer, "οὐδέν");
+        assert_eq!(session.history()[2].prompt.asked_at, Duration::from_secs(5));
+        assert_eq!(session.history()[2].answered_at, Duration::from_secs(12));
+
+        for _i in 0..10 {
+            session.next_prompt_with_rng(&mut rng).unwrap();
+            session.submit("");
+        }
+        assert_eq!(session.filter().len(), 4);
+
+        // each prompt changes a different param from the one before
+        session.options.avoid_last_change = true;
+        let mut last_change = vec![];
+        for _i in 0..20 {
+            let prompt = session.next_prompt_with_rng(&mut rng).unwrap().clone();
+            let change = changed_params(&prompt.prev, &prompt.form);
+            assert_eq!(change.len(), 1);
+            assert_ne!(change, last_change);
+            last_change = change;
+            session.submit(&prompt.answer);
+        }
+    }
+}
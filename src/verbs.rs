@@ -0,0 +1,415 @@
+use crate::curriculum::principal_part_for;
+use hoplite_verbs_rs::*;
+use rand::prelude::SliceRandom;
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerbType {
+    Omega,
+    Contract,
+    Mi,
+    Deponent,
+}
+
+// principal part n (1 to 6) without accents or breathings, None if missing or "—"
+fn bare_pp(verb: &HcGreekVerb, n: usize) -> Option<String> {
+    let pp = verb.pps.get(n - 1)?.trim();
+    if pp.is_empty() || pp == "—" {
+        return None;
+    }
+    Some(
+        pp.nfd()
+            .filter(|c| !('\u{0300}'..='\u{036f}').contains(c))
+            .collect(),
+    )
+}
+
+impl VerbType {
+    /// From the first principal part: -μι, -μαι, -άω/-έω/-όω, otherwise ω.
+    pub fn of(verb: &HcGreekVerb) -> VerbType {
+        let first = bare_pp(verb, 1).unwrap_or_default();
+        if first.ends_with("μι") {
+            VerbType::Mi
+        } else if first.ends_with("μαι") {
+            VerbType::Deponent
+        } else if ["αω", "εω", "οω"].iter().any(|e| first.ends_with(e)) {
+            VerbType::Contract
+        } else {
+            VerbType::Omega
+        }
+    }
+}
+
+/// Whether the first principal part is a perfect used as a present, like οἶδα or σύνοιδα:
+/// it ends in -α where other verbs have -ω, -μι or -μαι.
+pub fn is_perfect_as_present(verb: &HcGreekVerb) -> bool {
+    bare_pp(verb, 1).is_some_and(|pp| pp.ends_with('α'))
+}
+
+/// Whether the principal parts have forms for the tense and voice. Deponent parts
+/// (-μαι, -μην) have no active; a missing part ("—") has no forms at all.
+/// A perfect used as a present has no present or imperfect.
+pub fn has_tense_voice(verb: &HcGreekVerb, tense: HcTense, voice: HcVoice) -> bool {
+    if is_perfect_as_present(verb) && matches!(tense, HcTense::Present | HcTense::Imperfect) {
+        return false;
+    }
+    let deponent = |pp: &String| pp.ends_with("μαι") || pp.ends_with("μην");
+    let pp = principal_part_for(verb, tense, voice);
+    // the parts with an active, unless they are deponent
+    let active_pp = matches!(pp, 1..=3);
+    bare_pp(verb, pp.into())
+        .is_some_and(|pp| !(active_pp && voice == HcVoice::Active && deponent(&pp)))
+}
+
+/// The tenses and voices of parameters that the verb has any form for, so forms that
+/// cannot exist are never tried. Persons, numbers and moods are kept as they are.
+/// A tense and a voice that are each kept may still have no forms together, e.g. οἶδα's
+/// future active; check those with `has_tense_voice`.
+pub fn available_parameters(verb: &HcGreekVerb, parameters: &VerbParameters) -> VerbParameters {
+    let pairs: Vec<(HcTense, HcVoice)> = parameters
+        .tenses
+        .iter()
+        .flat_map(|t| parameters.voices.iter().map(move |v| (*t, *v)))
+        .filter(|(t, v)| has_tense_voice(verb, *t, *v))
+        .collect();
+    VerbParameters {
+        persons: parameters.persons.clone(),
+        numbers: parameters.numbers.clone(),
+        tenses: parameters
+            .tenses
+            .iter()
+            .copied()
+            .filter(|t| pairs.iter().any(|(pt, _)| pt == t))
+            .collect(),
+        voices: parameters
+            .voices
+            .iter()
+            .copied()
+            .filter(|v| pairs.iter().any(|(_, pv)| pv == v))
+            .collect(),
+        moods: parameters.moods.clone(),
+    }
+}
+
+/// A verb that may be chosen once its unit has been reached.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolVerb {
+    pub verb: Arc<HcGreekVerb>,
+    pub unit: i16,
+}
+
+impl PoolVerb {
+    // introduced in the verb's hq_unit
+    pub fn new(verb: Arc<HcGreekVerb>) -> PoolVerb {
+        let unit = verb.hq_unit as i16;
+        PoolVerb { verb, unit }
+    }
+}
+
+/// Chooses the next verb for a session from a pool.
+///
+/// Only verbs introduced by `highest_unit` are chosen. Recently used verbs are avoided, and
+/// a verb of a different type from the last one is preferred while there is one. With
+/// `weight_by_errors`, verbs a student often gets wrong come up more often.
+#[derive(Debug, Clone)]
+pub struct VerbChooser {
+    pub pool: Vec<PoolVerb>,
+    /// How many of the last verbs are avoided.
+    pub recent_len: usize,
+    pub mix_types: bool,
+    pub weight_by_errors: bool,
+    /// How much more often a verb that is always missed comes up than one never missed.
+    pub max_boost: f64,
+    recent: VecDeque<u32>,
+    answers: HashMap<u32, (u32, u32)>,
+}
+
+impl VerbChooser {
+    pub fn new(pool: Vec<PoolVerb>) -> VerbChooser {
+        VerbChooser {
+            pool,
+            recent_len: 3,
+            mix_types: true,
+            weight_by_errors: false,
+            max_boost: 3.0,
+            recent: VecDeque::new(),
+            answers: HashMap::new(),
+        }
+    }
+
+    pub fn recent(&self) -> &VecDeque<u32> {
+        &self.recent
+    }
+
+    pub fn record_answer(&mut self, verb_id: u32, correct: bool) {
+        let (wrong, total) = self.answers.entry(verb_id).or_default();
+        if !correct {
+            *wrong += 1;
+        }
+        *total += 1;
+    }
+
+    // share of wrong answers for the verb, None before any answer
+    pub fn error_rate(&self, verb_id: u32) -> Option<f64> {
+        self.answers
+            .get(&verb_id)
+            .filter(|(_, total)| *total > 0)
+            .map(|(wrong, total)| f64::from(*wrong) / f64::from(*total))
+    }
+
+    pub fn next_verb(&mut self, highest_unit: Option<i16>) -> Option<Arc<HcGreekVerb>> {
+        self.next_verb_with_rng(highest_unit, &mut rand::thread_rng())
+    }
+
+    pub fn next_verb_with_rng<R: Rng + ?Sized>(
+        &mut self,
+        highest_unit: Option<i16>,
+        rng: &mut R,
+    ) -> Option<Arc<HcGreekVerb>> {
+        let mut candidates: Vec<&PoolVerb> = self
+            .pool
+            .iter()
+            .filter(|v| highest_unit.is_none_or(|unit| v.unit <= unit))
+            .collect();
+
+        // each preference only applies while it leaves something to choose,
+        // so the most recent verbs are dropped first
+        for id in self.recent.iter().rev() {
+            if candidates.iter().any(|v| v.verb.id != *id) {
+                candidates.retain(|v| v.verb.id != *id);
+            }
+        }
+        if self.mix_types {
+            let last_type = self
+                .recent
+                .back()
+                .and_then(|id| self.pool.iter().find(|v| v.verb.id == *id))
+                .map(|v| VerbType::of(&v.verb));
+            let other_type: Vec<&PoolVerb> = candidates
+                .iter()
+                .copied()
+                .filter(|v| last_type.is_none_or(|t| VerbType::of(&v.verb) != t))
+                .collect();
+            if !other_type.is_empty() {
+                candidates = other_type;
+            }
+        }
+
+        let chosen = if self.weight_by_errors {
+            match candidates.choose_weighted(rng, |v| {
+                1.0 + (self.max_boost - 1.0) * self.error_rate(v.verb.id).unwrap_or(0.0)
+            }) {
+                Ok(v) => Some(v),
+                // weights that can't be sampled, e.g. from a negative or NaN max_boost
+                Err(_) => candidates.choose(rng),
+            }
+        } else {
+            candidates.choose(rng)
+        }?
+        .verb
+        .clone();
+
+        self.recent.push_back(chosen.id);
+        while self.recent.len() > self.recent_len {
+            self.recent.pop_front();
+        }
+        Some(chosen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn pool_verb(id: u32, pps: &str, unit: i16) -> PoolVerb {
+        PoolVerb {
+            verb: Arc::new(HcGreekVerb::from_string(id, pps, REGULAR, unit as u32).unwrap()),
+            unit,
+        }
+    }
+
+    #[test]
+    fn test_verb_type() {
+        let types = [
+            (
+                "παιδεύω, παιδεύσω, ἐπαίδευσα, πεπαίδευκα, πεπαίδευμαι, ἐπαιδεύθην",
+                VerbType::Omega,
+            ),
+            (
+                "ποιέω, ποιήσω, ἐποίησα, πεποίηκα, πεποίημαι, ἐποιήθην",
+                VerbType::Contract,
+            ),
+            (
+                "τῑμάω, τῑμήσω, ἐτίμησα, τετίμηκα, τετίμημαι, ἐτῑμήθην",
+                VerbType::Contract,
+            ),
+            ("δίδωμι, δώσω, ἔδωκα, δέδωκα, δέδομαι, ἐδόθην", VerbType::Mi),
+            (
+                "βούλομαι, βουλήσομαι, —, —, βεβούλημαι, ἐβουλήθην",
+                VerbType::Deponent,
+            ),
+        ];
+        for (pps, t) in types {
+            assert_eq!(VerbType::of(&pool_verb(1, pps, 2).verb), t, "{}", pps);
+        }
+    }
+
+    #[test]
+    fn test_available_parameters() {
+        let parameters = VerbParameters {
+            persons: vec![HcPerson::First, HcPerson::Second, HcPerson::Third],
+            numbers: vec![HcNumber::Singular, HcNumber::Plural],
+            tenses: vec![
+                HcTense::Present,
+                HcTense::Imperfect,
+                HcTense::Future,
+                HcTense::Aorist,
+                HcTense::Perfect,
+                HcTense::Pluperfect,
+            ],
+            voices: vec![HcVoice::Active, HcVoice::Middle, HcVoice::Passive],
+            moods: vec![HcMood::Indicative, HcMood::Subjunctive],
+        };
+
+        let luw = pool_verb(1, "λύω, λύσω, ἔλυσα, λέλυκα, λέλυμαι, ἐλύθην", 2);
+        let p = available_parameters(&luw.verb, &parameters);
+        assert_eq!(p.tenses, parameters.tenses);
+        assert_eq!(p.voices, parameters.voices);
+
+        let oida = pool_verb(2, "οἶδα, εἴσομαι, —, —, —, —", 2);
+        let p = available_parameters(&oida.verb, &parameters);
+        assert_eq!(
+            p.tenses,
+            vec![HcTense::Future, HcTense::Perfect, HcTense::Pluperfect]
+        );
+        assert_eq!(p.voices, vec![HcVoice::Active, HcVoice::Middle]);
+        assert!(!has_tense_voice(
+            &oida.verb,
+            HcTense::Future,
+            HcVoice::Active
+        ));
+        assert!(!has_tense_voice(
+            &oida.verb,
+            HcTense::Perfect,
+            HcVoice::Middle
+        ));
+
+        // a compound, known from its principal parts rather than its lemma
+        let synoida = pool_verb(4, "σύνοιδα, συνείσομαι, —, —, —, —", 2);
+        assert!(is_perfect_as_present(&synoida.verb));
+        assert!(!is_perfect_as_present(&luw.verb));
+        assert!(has_tense_voice(
+            &synoida.verb,
+            HcTense::Pluperfect,
+            HcVoice::Active
+        ));
+        assert!(!has_tense_voice(
+            &synoida.verb,
+            HcTense::Present,
+            HcVoice::Active
+        ));
+        assert_eq!(
+            available_parameters(&synoida.verb, &parameters).tenses,
+            vec![HcTense::Future, HcTense::Perfect, HcTense::Pluperfect]
+        );
+
+        let boulomai = pool_verb(3, "βούλομαι, βουλήσομαι, —, —, βεβούλημαι, ἐβουλήθην", 2);
+        assert!(!has_tense_voice(
+            &boulomai.verb,
+            HcTense::Present,
+            HcVoice::Active
+        ));
+        assert!(has_tense_voice(
+            &boulomai.verb,
+            HcTense::Present,
+            HcVoice::Middle
+        ));
+        assert!(!has_tense_voice(
+            &boulomai.verb,
+            HcTense::Aorist,
+            HcVoice::Middle
+        ));
+        assert!(has_tense_voice(
+            &boulomai.verb,
+            HcTense::Aorist,
+            HcVoice::Passive
+        ));
+
+        // only the active of a deponent asked for: nothing is left
+        let active = VerbParameters {
+            voices: vec![HcVoice::Active],
+            ..available_parameters(&luw.verb, &parameters)
+        };
+        let p = available_parameters(&boulomai.verb, &active);
+        assert!(p.tenses.is_empty() && p.voices.is_empty());
+    }
+
+    #[test]
+    fn test_next_verb() {
+        let mut chooser = VerbChooser::new(vec![
+            pool_verb(
+                1,
+                "παιδεύω, παιδεύσω, ἐπαίδευσα, πεπαίδευκα, πεπαίδευμαι, ἐπαιδεύθην",
+                2,
+            ),
+            pool_verb(2, "λύω, λύσω, ἔλυσα, λέλυκα, λέλυμαι, ἐλύθην", 2),
+            pool_verb(
+                3,
+                "ποιέω, ποιήσω, ἐποίησα, πεποίηκα, πεποίημαι, ἐποιήθην",
+                4,
+            ),
+            pool_verb(4, "δίδωμι, δώσω, ἔδωκα, δέδωκα, δέδομαι, ἐδόθην", 11),
+        ]);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // nothing after the unit, and not the same verb twice in a row
+        let mut last = 0;
+        for _i in 0..20 {
+            let v = chooser.next_verb_with_rng(Some(2), &mut rng).unwrap();
+            assert!(v.id == 1 || v.id == 2);
+            assert_ne!(v.id, last);
+            last = v.id;
+        }
+
+        // an ω verb is followed by the contract verb while it isn't recent
+        chooser.recent_len = 1;
+        for _i in 0..20 {
+            let v = chooser.next_verb_with_rng(Some(4), &mut rng).unwrap();
+            let next = chooser.next_verb_with_rng(Some(4), &mut rng).unwrap();
+            if v.id != 3 {
+                assert_eq!(next.id, 3);
+            }
+        }
+        assert_eq!(chooser.next_verb_with_rng(Some(1), &mut rng), None);
+
+        // missed verbs come up more often
+        let mut chooser = VerbChooser::new(chooser.pool.clone());
+        chooser.recent_len = 0;
+        chooser.weight_by_errors = true;
+        chooser.record_answer(2, false);
+        chooser.record_answer(1, true);
+        assert_eq!(chooser.error_rate(2), Some(1.0));
+        assert_eq!(chooser.error_rate(3), None);
+        let count = 10_000;
+        let mut missed = 0;
+        for _i in 0..count {
+            if chooser.next_verb_with_rng(Some(2), &mut rng).unwrap().id == 2 {
+                missed += 1;
+            }
+        }
+        // 3 / (1 + 3)
+        let share = missed as f64 / count as f64;
+        assert!(share > 0.72 && share < 0.78, "{}", share);
+
+        // a max_boost that gives invalid weights still chooses a verb
+        for max_boost in [-1.0, f64::NAN] {
+            chooser.max_boost = max_boost;
+            assert!(chooser.next_verb_with_rng(Some(2), &mut rng).is_some());
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::FormParam;
+use hoplite_verbs_rs::*;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::prelude::SliceRandom;
+use rand::Rng;
+
+/// Relative weights for which parameters `change_params` changes, and which values it
+/// changes them to. A weight of 0 means the parameter or value is never chosen; values
+/// missing from a list have weight 1. Uniform weights, such as the defaults, change finite
+/// forms exactly as the unweighted `change_params` did.
+/// If the weights for a choice are negative, not finite or too large to add up, that
+/// choice is made uniformly instead.
+///
+/// `change_params` and `random_form` always use the default weights; to pass weights,
+/// set `RandomFormOptions::weights` for `change_params_with_options`,
+/// `random_form_with_options` and `reachable_forms`.
+///
+/// e.g. to change tense half the time and prefer the aorist and perfect:
+/// `ParamWeights { tense: 4.0, tenses: vec![(HcTense::Aorist, 3.0), (HcTense::Perfect, 3.0)], ..Default::default() }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamWeights {
+    pub person: f64,
+    pub number: f64,
+    pub tense: f64,
+    pub mood: f64,
+    pub voice: f64,
+    /// A participle's gender and case, which have no value weights.
+    pub declension: f64,
+    pub persons: Vec<(HcPerson, f64)>,
+    pub numbers: Vec<(HcNumber, f64)>,
+    pub tenses: Vec<(HcTense, f64)>,
+    pub moods: Vec<(HcMood, f64)>,
+    pub voices: Vec<(HcVoice, f64)>,
+}
+
+impl Default for ParamWeights {
+    fn default() -> Self {
+        ParamWeights {
+            person: 1.0,
+            number: 1.0,
+            tense: 1.0,
+            mood: 1.0,
+            voice: 1.0,
+            declension: 1.0,
+            persons: vec![],
+            numbers: vec![],
+            tenses: vec![],
+            moods: vec![],
+            voices: vec![],
+        }
+    }
+}
+
+pub fn value_weight<T: PartialEq>(weights: &[(T, f64)], value: &T) -> f64 {
+    weights
+        .iter()
+        .find(|(v, _)| v == value)
+        .map_or(1.0, |(_, w)| *w)
+}
+
+// choose one of values in proportion to its weight, None if none can be chosen
+// equal weights draw from the rng exactly as an unweighted choose does
+pub(crate) fn choose_value<'a, T: PartialEq, R: Rng + ?Sized>(
+    values: &[&'a T],
+    weights: &[(T, f64)],
+    rng: &mut R,
+) -> Option<&'a T> {
+    let w: Vec<f64> = values.iter().map(|v| value_weight(weights, v)).collect();
+    if w.first()
+        .is_some_and(|first| *first > 0.0 && w.iter().all(|x| x == first))
+    {
+        return values.choose(rng).copied();
+    }
+    let allowed: Vec<(&&T, f64)> = values.iter().zip(w).filter(|(_, w)| *w > 0.0).collect();
+    match weighted_index(allowed.iter().map(|(_, w)| *w)) {
+        Some(dist) => Some(*allowed[dist.sample(rng)].0),
+        None if allowed.is_empty() => None,
+        None => allowed.choose(rng).map(|(v, _)| **v),
+    }
+}
+
+// None if the weights can't be sampled from; WeightedIndex panics when their sum isn't finite
+fn weighted_index(weights: impl Iterator<Item = f64> + Clone) -> Option<WeightedIndex<f64>> {
+    if !weights.clone().sum::<f64>().is_finite() {
+        return None;
+    }
+    WeightedIndex::new(weights).ok()
+}
+
+impl ParamWeights {
+    pub fn weight(&self, param: &FormParam) -> f64 {
+        match param {
+            FormParam::Param(HcParameters::Person) => self.person,
+            FormParam::Param(HcParameters::Number) => self.number,
+            FormParam::Param(HcParameters::Tense) => self.tense,
+            FormParam::Param(HcParameters::Mood) => self.mood,
+            FormParam::Param(HcParameters::Voice) => self.voice,
+            FormParam::Declension => self.declension,
+        }
+    }
+
+    pub fn set_weight(&mut self, param: &FormParam, weight: f64) {
+        match param {
+            FormParam::Param(HcParameters::Person) => self.person = weight,
+            FormParam::Param(HcParameters::Number) => self.number = weight,
+            FormParam::Param(HcParameters::Tense) => self.tense = weight,
+            FormParam::Param(HcParameters::Mood) => self.mood = weight,
+            FormParam::Param(HcParameters::Voice) => self.voice = weight,
+            FormParam::Declension => self.declension = weight,
+        }
+    }
+
+    // false if every other allowed value of param has weight 0
+    pub fn can_change(
+        &self,
+        vf: &HcGreekVerbForm,
+        param: &FormParam,
+        parameters: &VerbParameters,
+    ) -> bool {
+        let FormParam::Param(param) = param else {
+            return true;
+        };
+        match param {
+            HcParameters::Person => parameters
+                .persons
+                .iter()
+                .any(|x| Some(*x) != vf.person && value_weight(&self.persons, x) > 0.0),
+            HcParameters::Number => parameters
+                .numbers
+                .iter()
+                .any(|x| Some(*x) != vf.number && value_weight(&self.numbers, x) > 0.0),
+            HcParameters::Tense => parameters
+                .tenses
+                .iter()
+                .any(|x| *x != vf.tense && value_weight(&self.tenses, x) > 0.0),
+            HcParameters::Mood => parameters
+                .moods
+                .iter()
+                .any(|x| *x != vf.mood && value_weight(&self.moods, x) > 0.0),
+            HcParameters::Voice => parameters
+                .voices
+                .iter()
+                .any(|x| *x != vf.voice && value_weight(&self.voices, x) > 0.0),
+        }
+    }
+
+    pub fn has_value_weights(&self) -> bool {
+        !self.persons.is_empty()
+            || !self.numbers.is_empty()
+            || !self.tenses.is_empty()
+            || !self.moods.is_empty()
+            || !self.voices.is_empty()
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        [
+            self.number,
+            self.tense,
+            self.mood,
+            self.voice,
+            self.declension,
+        ]
+        .iter()
+        .all(|w| *w == self.person)
+            && self.person > 0.0
+    }
+
+    // choose up to n params without replacement, in proportion to their weights
+    // uniform weights draw from the rng exactly as an unweighted shuffle does
+    pub(crate) fn choose_params<R: Rng + ?Sized>(
+        &self,
+        mut params: Vec<FormParam>,
+        n: usize,
+        rng: &mut R,
+    ) -> Vec<FormParam> {
+        if self.is_uniform() {
+            params.shuffle(rng);
+            params.truncate(n);
+            return params;
+        }
+
+        params.retain(|p| self.weight(p) > 0.0);
+        let mut chosen = vec![];
+        while chosen.len() < n && !params.is_empty() {
+            let i = match weighted_index(params.iter().map(|p| self.weight(p))) {
+                Some(dist) => dist.sample(rng),
+                None => rng.gen_range(0..params.len()),
+            };
+            chosen.push(params.remove(i));
+        }
+        chosen
+    }
+}